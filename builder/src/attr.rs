@@ -0,0 +1,574 @@
+use proc_macro2::{Span, TokenTree};
+use quote::ToTokens;
+use std::cell::RefCell;
+use syn::meta::ParseNestedMeta;
+use syn::parse::ParseStream;
+use syn::spanned::Spanned;
+use syn::{
+    token, Attribute, Error, Expr, Ident, Lit, LitBool, LitStr, Path, Result, Token, Type,
+    Visibility,
+};
+
+/// Options given by `#[builder(...)]` on the struct itself.
+#[derive(Clone, Default)]
+pub struct ContainerAttrs {
+    /// With `no_std`, allow the generated code to use the `alloc` crate.
+    pub alloc: bool,
+    /// Name of the method which builds the struct, in place of `build`.
+    pub build_fn: Option<Ident>,
+    /// Name of the associated function which returns a new builder, in place
+    /// of `builder`.
+    pub constructor: Option<Ident>,
+    /// Generate a `get_` method per field, and methods telling which
+    /// required fields are still unset.
+    pub getters: bool,
+    /// Path under which the generated code finds `core`, `alloc` or `std`,
+    /// and `serde`, for use through a crate re-exporting the derive.
+    pub krate: Option<Path>,
+    /// Fields left unset take their value from the struct's own `Default`
+    /// impl.
+    pub default: bool,
+    /// Error type returned by `build()` in place of the generated one. It
+    /// must implement `From` the generated error type.
+    pub error: Option<Type>,
+    /// Name of the builder type, in place of `{Struct}Builder`.
+    pub name: Option<Ident>,
+    /// Generate `merge()`, taking the fields set on another builder. Implied
+    /// by `serde`, as deserialized builders are meant to be layered.
+    pub merge: bool,
+    /// Generate code which only depends on `core`, and `alloc` if allowed.
+    pub no_std: bool,
+    /// How setters and `build()` take the builder.
+    pub pattern: Option<Pattern>,
+    /// Derive `serde::Deserialize` for the builder.
+    pub serde: bool,
+    /// Default for every field's `setter(into)`.
+    pub setter_into: bool,
+    /// Track required fields in the builder's type so that `build()` only
+    /// exists once every one of them has been set.
+    pub typestate: bool,
+    /// Name of the method returning a builder holding a copy of the struct,
+    /// in place of `to_builder`.
+    pub to_builder: Option<Ident>,
+    /// With `to_builder = false`, leave that method out.
+    pub skip_to_builder: bool,
+    /// Where `to_builder` was given, to report it on an enum.
+    pub to_builder_span: Option<Span>,
+    /// Function run by `build()` on the assembled struct to check invariants
+    /// spanning several fields.
+    pub validate: Option<Path>,
+    /// Visibility of the builder, its constructor and its error type, in
+    /// place of the struct's own.
+    pub vis: Option<Visibility>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Pattern {
+    /// Setters take and return `self`; `build(self)` moves the values out.
+    Owned,
+    /// Setters take and return `&mut self`; `build(&self)` clones.
+    Mutable,
+    /// Setters take `&self` and return a modified clone; `build(&self)`
+    /// clones.
+    Immutable,
+}
+
+/// Options given by `#[builder(...)]` on a single field.
+pub struct FieldAttrs {
+    /// Name of the setter which appends one element at a time.
+    pub each: Option<Ident>,
+    /// Value to use if the field is never set.
+    pub default: Option<DefaultValue>,
+    /// Name of the setter, replacing the field's own name or the `_0`, `_1`,
+    /// ... used for tuple fields.
+    pub name: Option<Ident>,
+    /// `setter(into)`: setters accept `impl Into<T>`.
+    pub setter_into: bool,
+    /// `setter(strip_option = false)`: the setter of an `Option<T>` field takes
+    /// the `Option<T>` itself. The value is kept as given, to report it on a
+    /// field which is not an `Option`.
+    pub strip_option: Option<LitBool>,
+    /// `setter(skip)`: no setter, the field is always defaulted.
+    pub skip: bool,
+    /// The field is built by a nested builder, of the type given or else of
+    /// the field's type name followed by `Builder`.
+    pub sub_builder: Option<Option<Type>>,
+    /// Also generate a `try_` setter which runs the field's validation
+    /// function straight away.
+    pub try_setter: bool,
+    /// Function checking a value given to the field's setter.
+    pub validate: Option<Path>,
+}
+
+pub enum DefaultValue {
+    /// `#[builder(default)]`
+    Trait,
+    /// `#[builder(default = "expr")]`, along with the expression as written.
+    Expr(Expr, String),
+    /// `#[builder(default_with = "path")]`: an async function producing the
+    /// value.
+    With(Path),
+    /// `#[builder(try_default = "path")]`: a function returning a `Result`
+    /// of the value.
+    Try(Path),
+}
+
+const CONTAINER_KEYS: &[&str] = &[
+    "alloc",
+    "build_fn",
+    "constructor",
+    "crate",
+    "default",
+    "error",
+    "getters",
+    "merge",
+    "name",
+    "no_std",
+    "pattern",
+    "serde",
+    "setter",
+    "to_builder",
+    "typestate",
+    "validate",
+    "vis",
+];
+
+const CONTAINER_SETTER_KEYS: &[&str] = &["into"];
+
+const FIELD_KEYS: &[&str] = &[
+    "default",
+    "default_with",
+    "each",
+    "name",
+    "setter",
+    "sub_builder",
+    "try_default",
+    "try_setter",
+    "validate",
+];
+
+const FIELD_SETTER_KEYS: &[&str] = &["into", "skip", "strip_option"];
+
+/// Errors found in the input, collected so that they are all reported by one
+/// compilation rather than one at a time.
+#[derive(Default)]
+pub struct Errors(RefCell<Option<Error>>);
+
+impl Errors {
+    pub fn push(&self, error: Error) {
+        let mut errors = self.0.borrow_mut();
+        match &mut *errors {
+            Some(errors) => errors.combine(error),
+            None => *errors = Some(error),
+        }
+    }
+
+    /// `result` if nothing went wrong, otherwise every error collected along
+    /// with the one `result` may hold.
+    pub fn finish<T>(self, result: Result<T>) -> Result<T> {
+        let errors = self.0.into_inner();
+        match (errors, result) {
+            (None, result) => result,
+            (Some(errors), Ok(_)) => Err(errors),
+            (Some(mut errors), Err(error)) => {
+                errors.combine(error);
+                Err(errors)
+            }
+        }
+    }
+}
+
+/// The keys allowed in one parenthesized list, such as `builder(...)` or
+/// `setter(...)`, and the ones seen so far across every occurrence of it.
+struct Keys<'a> {
+    list: &'static str,
+    known: &'static [&'static str],
+    seen: Vec<String>,
+    errors: &'a Errors,
+}
+
+impl<'a> Keys<'a> {
+    fn new(list: &'static str, known: &'static [&'static str], errors: &'a Errors) -> Self {
+        Keys {
+            list,
+            known,
+            seen: Vec::new(),
+            errors,
+        }
+    }
+
+    /// Passes the key of `meta` to `parse` along with `meta` itself, unless
+    /// the key is unknown or already given. Errors are recorded rather than
+    /// returned, and the rest of the entry is skipped, so that parsing carries
+    /// on with the next key.
+    fn parse<F>(&mut self, meta: ParseNestedMeta, parse: F) -> Result<()>
+    where
+        F: FnOnce(&str, &ParseNestedMeta) -> Result<()>,
+    {
+        let key = match meta.path.get_ident() {
+            Some(ident) => ident.to_string(),
+            None => meta.path.to_token_stream().to_string().replace(' ', ""),
+        };
+        let list = self.list;
+
+        if !self.known.contains(&key.as_str()) {
+            let message = match suggest(&key, self.known) {
+                Some(known) => format!(
+                    "unknown `{}` key `{}`, did you mean `{}`?",
+                    list, key, known
+                ),
+                None => format!(
+                    "unknown `{}` key `{}`, expected one of {}",
+                    list,
+                    key,
+                    self.known
+                        .iter()
+                        .map(|known| format!("`{}`", known))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                ),
+            };
+            self.errors.push(Error::new_spanned(&meta.path, message));
+            return skip_value(meta.input);
+        }
+
+        // A nested list may be split over several occurrences, its own keys
+        // are the ones which must not repeat.
+        if !meta.input.peek(token::Paren) {
+            if self.seen.contains(&key) {
+                self.errors.push(Error::new_spanned(
+                    &meta.path,
+                    format!("duplicate `{}` key `{}`", list, key),
+                ));
+                return skip_value(meta.input);
+            }
+            self.seen.push(key.clone());
+        }
+
+        if let Err(error) = parse(&key, &meta) {
+            self.errors.push(error);
+            return skip_value(meta.input);
+        }
+        if !meta.input.is_empty() && !meta.input.peek(Token![,]) {
+            self.errors.push(
+                meta.input
+                    .error(format!("unexpected value for `{}` key `{}`", list, key)),
+            );
+            return skip_value(meta.input);
+        }
+        Ok(())
+    }
+}
+
+/// Skips what is left of an entry after an error, up to the next comma.
+fn skip_value(input: ParseStream) -> Result<()> {
+    while !input.is_empty() && !input.peek(Token![,]) {
+        input.parse::<TokenTree>()?;
+    }
+    Ok(())
+}
+
+/// The known key closest to a misspelled `key`, if any is close enough to
+/// be what was meant.
+fn suggest(key: &str, known: &[&'static str]) -> Option<&'static str> {
+    known
+        .iter()
+        .map(|known| (edit_distance(key, known), *known))
+        .filter(|(distance, _)| *distance <= 2 && *distance < key.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
+
+/// Levenshtein distance between `a` and `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != *b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Calls `parse` for every entry of every `#[builder(...)]` in `attrs`.
+fn parse_builder_attrs<F>(attrs: &[Attribute], keys: &mut Keys, mut parse: F)
+where
+    F: FnMut(&str, &ParseNestedMeta) -> Result<()>,
+{
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("builder")) {
+        if let Err(error) = attr.parse_nested_meta(|meta| keys.parse(meta, &mut parse)) {
+            keys.errors.push(error);
+        }
+    }
+}
+
+pub fn container(attrs: &[Attribute], errors: &Errors) -> ContainerAttrs {
+    let mut container = ContainerAttrs::default();
+    let mut pattern_lit = None;
+    let mut serde_path = None;
+    let mut alloc_path = None;
+    let mut getters_path = None;
+    let mut merge_path = None;
+
+    let mut keys = Keys::new("builder", CONTAINER_KEYS, errors);
+    let mut setter_keys = Keys::new("setter", CONTAINER_SETTER_KEYS, errors);
+    parse_builder_attrs(attrs, &mut keys, |key, meta| {
+        match key {
+            "alloc" => {
+                container.alloc = true;
+                alloc_path = Some(meta.path.clone());
+            }
+            "build_fn" => {
+                let name: LitStr = meta.value()?.parse()?;
+                container.build_fn = Some(name.parse()?);
+            }
+            "constructor" => {
+                let name: LitStr = meta.value()?.parse()?;
+                container.constructor = Some(name.parse()?);
+            }
+            "crate" => {
+                let path: LitStr = meta.value()?.parse()?;
+                container.krate = Some(path.parse()?);
+            }
+            "default" => container.default = true,
+            "error" => {
+                let ty: LitStr = meta.value()?.parse()?;
+                container.error = Some(ty.parse()?);
+            }
+            "getters" => {
+                container.getters = true;
+                getters_path = Some(meta.path.clone());
+            }
+            "name" => {
+                let name: LitStr = meta.value()?.parse()?;
+                container.name = Some(name.parse()?);
+            }
+            "merge" => {
+                container.merge = true;
+                merge_path = Some(meta.path.clone());
+            }
+            "no_std" => container.no_std = true,
+            "pattern" => {
+                let pattern: LitStr = meta.value()?.parse()?;
+                container.pattern = Some(match pattern.value().as_str() {
+                    "owned" => Pattern::Owned,
+                    "mutable" => Pattern::Mutable,
+                    "immutable" => Pattern::Immutable,
+                    _ => {
+                        return Err(Error::new_spanned(
+                            pattern,
+                            "expected `owned`, `mutable` or `immutable`",
+                        ))
+                    }
+                });
+                pattern_lit = Some(pattern);
+            }
+            "serde" => {
+                if !cfg!(feature = "serde") {
+                    return Err(meta
+                        .error("`builder(serde)` requires the `serde` feature of derive_builder"));
+                }
+                container.serde = true;
+                container.merge = true;
+                serde_path = Some(meta.path.clone());
+            }
+            "setter" => meta.parse_nested_meta(|meta| {
+                setter_keys.parse(meta, |key, _| {
+                    match key {
+                        "into" => container.setter_into = true,
+                        _ => unreachable!(),
+                    }
+                    Ok(())
+                })
+            })?,
+            "to_builder" => {
+                container.to_builder_span = Some(meta.path.span());
+                match meta.value()?.parse()? {
+                    Lit::Bool(enabled) => container.skip_to_builder = !enabled.value,
+                    Lit::Str(name) => container.to_builder = Some(name.parse()?),
+                    lit => return Err(Error::new_spanned(
+                        lit,
+                        "expected `false` or the name of the method, as in `to_builder = \"...\"`",
+                    )),
+                }
+            }
+            "typestate" => container.typestate = true,
+            "validate" => {
+                let path: LitStr = meta.value()?.parse()?;
+                container.validate = Some(path.parse()?);
+            }
+            "vis" => {
+                let vis: LitStr = meta.value()?.parse()?;
+                container.vis = Some(vis.parse()?);
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    });
+
+    if let Some(pattern) = pattern_lit {
+        if container.typestate && container.pattern != Some(Pattern::Owned) {
+            errors.push(Error::new_spanned(
+                pattern,
+                "a typestate builder always uses `pattern = \"owned\"`",
+            ));
+        }
+    }
+
+    if let Some(serde) = serde_path {
+        if container.typestate {
+            errors.push(Error::new_spanned(
+                serde,
+                "a typestate builder cannot be deserialized",
+            ));
+        }
+    }
+
+    if let Some(merge) = merge_path {
+        if container.typestate {
+            errors.push(Error::new_spanned(
+                merge,
+                "a typestate builder cannot be merged, its type tells which fields are set",
+            ));
+        }
+    }
+
+    if let Some(getters) = getters_path {
+        if container.typestate {
+            errors.push(Error::new_spanned(
+                getters,
+                "a typestate builder has no getters, its type tells which fields are set",
+            ));
+        }
+    }
+
+    if let Some(alloc) = alloc_path {
+        if !container.no_std {
+            errors.push(Error::new_spanned(
+                alloc,
+                "`builder(alloc)` only applies together with `builder(no_std)`",
+            ));
+        }
+    }
+
+    container
+}
+
+pub fn field(attrs: &[Attribute], errors: &Errors) -> FieldAttrs {
+    let mut field = FieldAttrs {
+        each: None,
+        default: None,
+        name: None,
+        setter_into: false,
+        strip_option: None,
+        skip: false,
+        sub_builder: None,
+        try_setter: false,
+        validate: None,
+    };
+
+    let mut into_path = None;
+    let mut keys = Keys::new("builder", FIELD_KEYS, errors);
+    let mut setter_keys = Keys::new("setter", FIELD_SETTER_KEYS, errors);
+    parse_builder_attrs(attrs, &mut keys, |key, meta| {
+        if key.contains("default") && field.default.is_some() {
+            return Err(Error::new_spanned(
+                &meta.path,
+                "only one of `default`, `default_with` and `try_default` can be given",
+            ));
+        }
+        match key {
+            "default" => {
+                field.default = Some(if meta.input.peek(Token![=]) {
+                    let expr: LitStr = meta.value()?.parse()?;
+                    DefaultValue::Expr(expr.parse()?, expr.value())
+                } else {
+                    DefaultValue::Trait
+                });
+            }
+            "default_with" => {
+                let path: LitStr = meta.value()?.parse()?;
+                field.default = Some(DefaultValue::With(path.parse()?));
+            }
+            "each" => {
+                let name: LitStr = meta.value()?.parse()?;
+                field.each = Some(name.parse()?);
+            }
+            "name" => {
+                let name: LitStr = meta.value()?.parse()?;
+                field.name = Some(name.parse()?);
+            }
+            "setter" => meta.parse_nested_meta(|meta| {
+                setter_keys.parse(meta, |key, meta| {
+                    if key == "into" {
+                        into_path = Some(meta.path.clone());
+                    }
+                    field_setter(&mut field, key, meta)
+                })
+            })?,
+            "sub_builder" => {
+                field.sub_builder = Some(if meta.input.peek(Token![=]) {
+                    let ty: LitStr = meta.value()?.parse()?;
+                    Some(ty.parse()?)
+                } else {
+                    None
+                });
+            }
+            "try_default" => {
+                let path: LitStr = meta.value()?.parse()?;
+                field.default = Some(DefaultValue::Try(path.parse()?));
+            }
+            "try_setter" => field.try_setter = true,
+            "validate" => {
+                let path: LitStr = meta.value()?.parse()?;
+                field.validate = Some(path.parse()?);
+            }
+            _ => unreachable!(),
+        }
+        Ok(())
+    });
+
+    // Neither a skipped field nor a sub-builder has a setter for these to
+    // apply to.
+    let setterless = if field.skip {
+        Some("setter(skip)")
+    } else if field.sub_builder.is_some() {
+        Some("sub_builder")
+    } else {
+        None
+    };
+    if let Some(setterless) = setterless {
+        if let Some(into) = into_path {
+            errors.push(Error::new_spanned(
+                into,
+                format!("`setter(into)` cannot be combined with `{}`", setterless),
+            ));
+        }
+        if let Some(strip) = &field.strip_option {
+            errors.push(Error::new_spanned(
+                strip,
+                format!(
+                    "`setter(strip_option)` cannot be combined with `{}`",
+                    setterless,
+                ),
+            ));
+        }
+    }
+
+    field
+}
+
+fn field_setter(field: &mut FieldAttrs, key: &str, meta: &ParseNestedMeta) -> Result<()> {
+    match key {
+        "into" => field.setter_into = true,
+        "strip_option" => field.strip_option = Some(meta.value()?.parse()?),
+        "skip" => field.skip = true,
+        _ => unreachable!(),
+    }
+    Ok(())
+}
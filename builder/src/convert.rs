@@ -0,0 +1,139 @@
+//! Turning a struct back into a builder.
+//!
+//! `From<Struct>` moves every field of the struct into a builder as if its
+//! setter had been called, and `to_builder(&self)` does the same with clones.
+//! The latter can be renamed or left out, for structs with a method of that
+//! name already.
+//! The builder of a typestate struct comes out with every required field
+//! marked as set, so it can be built again straight away. Enums have no
+//! conversion, as a variant builder cannot hold any other variant.
+
+use crate::model::{Builder, Field, Kind};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_quote, WherePredicate};
+
+pub fn expand(builder: &Builder) -> Option<TokenStream> {
+    let core = builder.core();
+    if builder.variant.is_some() {
+        return None;
+    }
+
+    let Builder {
+        vis,
+        target,
+        ident: builder_ident,
+        generics,
+        fields,
+        ..
+    } = builder;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let target_ty = builder.target_ty();
+
+    let target_args = builder.target_args();
+    let builder_ty = if builder.attrs.typestate {
+        let states = fields
+            .iter()
+            .filter(|field| field.is_required())
+            .map(|field| {
+                let ty = field.ty;
+                quote!((#ty,))
+            });
+        quote!(#builder_ident<#(#target_args,)* #(#states),*>)
+    } else {
+        quote!(#builder_ident #ty_generics)
+    };
+
+    let stored = || fields.iter().filter(|field| field.is_stored());
+    let moved = stored().map(|field| {
+        let member = &field.member;
+        init(builder, field, quote!(__value.#member))
+    });
+    let cloned = stored().map(|field| {
+        let member = &field.member;
+        init(
+            builder,
+            field,
+            quote!(#core::clone::Clone::clone(&self.#member)),
+        )
+    });
+    // A bound on a type without parameters, such as `File: Clone`, is an
+    // error where the struct is defined unless it holds. Binding a lifetime
+    // defers the check to callers of to_builder(), so that structs with fields
+    // which cannot be cloned still get a `From` impl.
+    let clone_bounds = stored().map(|field| {
+        let ty = field.ty;
+        quote!(for<'__a> #ty: #core::clone::Clone)
+    });
+    // The same goes for a nested builder which cannot be made from its
+    // struct, a typestate one, whose use is reported at the field instead.
+    let from_bounds: Vec<TokenStream> = stored()
+        .filter_map(|field| match &field.kind {
+            Kind::SubBuilder(builder_ty) => {
+                let ty = field.ty;
+                Some(quote!(for<'__a> #builder_ty: #core::convert::From<#ty>))
+            }
+            _ => None,
+        })
+        .collect();
+    let to_builder = if builder.attrs.skip_to_builder {
+        None
+    } else {
+        let name = builder
+            .attrs
+            .to_builder
+            .clone()
+            .unwrap_or_else(|| format_ident!("to_builder"));
+        Some(quote! {
+            impl #impl_generics #target #ty_generics #where_clause {
+                /// Returns a builder holding a copy of every field of `self`.
+                #vis fn #name(&self) -> #builder_ty
+                where
+                    #(#clone_bounds,)*
+                    #(#from_bounds,)*
+                {
+                    #builder_ident {
+                        #(#cloned,)*
+                        __marker: #core::marker::PhantomData,
+                    }
+                }
+            }
+        })
+    };
+
+    let mut from_generics = (*generics).clone();
+    from_generics.make_where_clause().predicates.extend(
+        from_bounds
+            .iter()
+            .map(|bound| -> WherePredicate { parse_quote!(#bound) }),
+    );
+    let (_, _, from_where_clause) = from_generics.split_for_impl();
+
+    Some(quote! {
+        impl #impl_generics #core::convert::From<#target_ty> for #builder_ty #from_where_clause {
+            fn from(__value: #target_ty) -> Self {
+                #builder_ident {
+                    #(#moved,)*
+                    __marker: #core::marker::PhantomData,
+                }
+            }
+        }
+
+        #to_builder
+    })
+}
+
+/// Initializer of the builder's storage for `field` from its `value`.
+fn init(builder: &Builder, field: &Field, value: TokenStream) -> TokenStream {
+    let core = builder.core();
+    let ident = &field.ident;
+    if builder.attrs.typestate && field.is_required() {
+        return quote!(#ident: (#value,));
+    }
+    match field.kind {
+        Kind::SubBuilder(_) => quote! {
+            #ident: #core::option::Option::Some(#core::convert::From::from(#value))
+        },
+        _ => quote!(#ident: #core::option::Option::Some(#value)),
+    }
+}
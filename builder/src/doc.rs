@@ -0,0 +1,239 @@
+//! Documentation of the generated items.
+//!
+//! Setters and accessors carry the doc comments of their field, so that the
+//! builder's API reads like the struct's own. Everything else public gets a
+//! generated description, which also keeps crates denying `missing_docs`
+//! building.
+
+use crate::model::{Builder, Fallback, Field, Kind};
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::LitStr;
+
+/// Docs of the builder type: what it builds, and which fields it needs.
+pub fn builder(builder: &Builder) -> TokenStream {
+    let target = target_name(builder);
+    let mut lines = vec![format!(
+        "Builder for [`{}`], created by [`{}::{}()`].",
+        target,
+        builder.target,
+        builder.constructor.unraw(),
+    )];
+
+    let settable = || builder.fields.iter().filter(|field| field.is_stored());
+    let required: Vec<String> = settable()
+        .filter(|field| field.is_required())
+        .map(|field| format!("- `{}`", field.ident.unraw()))
+        .collect();
+    if !required.is_empty() {
+        lines.push(String::new());
+        lines.push(format!(
+            "Required fields, which must be set before calling [`{0}()`](Self::{0}):",
+            builder.build_fn.unraw(),
+        ));
+        lines.extend(required);
+    }
+
+    let optional: Vec<String> = settable()
+        .filter(|field| !field.is_required())
+        .map(|field| format!("- `{}`: {}", field.ident.unraw(), fallback(builder, field)))
+        .collect();
+    if !optional.is_empty() {
+        lines.push(String::new());
+        lines.push("Optional fields, and the value they take when not set:".to_owned());
+        lines.extend(optional);
+    }
+
+    attrs(lines)
+}
+
+/// Docs of the associated function returning a new builder.
+pub fn constructor(builder: &Builder) -> TokenStream {
+    attrs(vec![format!(
+        "Returns a [`{}`] with no field set.",
+        builder.ident,
+    )])
+}
+
+/// Docs of `build()`, naming the ways it can fail.
+pub fn build_fn(builder: &Builder) -> TokenStream {
+    let first = if builder.attrs.typestate {
+        format!(
+            "Builds the [`{}`], once every required field has been set.",
+            target_name(builder),
+        )
+    } else {
+        format!(
+            "Builds a [`{}`] from the fields set so far.",
+            target_name(builder),
+        )
+    };
+    attrs(with_failures(
+        builder,
+        first,
+        builder.fields.iter().any(|field| field.can_be_missing()),
+    ))
+}
+
+/// Docs of `build_async()`.
+pub fn build_async(builder: &Builder) -> TokenStream {
+    let first = format!(
+        "Builds a [`{}`] from the fields set so far, awaiting the defaults of the others which need it.",
+        target_name(builder),
+    );
+    attrs(with_failures(
+        builder,
+        first,
+        builder.fields.iter().any(|field| field.is_required()),
+    ))
+}
+
+/// `first` followed by the ways building can fail, `missing` telling whether
+/// a field can be missing.
+fn with_failures(builder: &Builder, first: String, missing: bool) -> Vec<String> {
+    let mut lines = vec![first];
+    let fields = &builder.fields;
+    let mut failures = Vec::new();
+    if !builder.attrs.typestate && missing {
+        failures.push("a required field was never set");
+    }
+    if builder.attrs.validate.is_some() || fields.iter().any(|field| field.validate.is_some()) {
+        failures.push("a validation function rejects a value");
+    }
+    if fields
+        .iter()
+        .any(|field| matches!(field.kind, Kind::SubBuilder(_)))
+    {
+        failures.push("a sub-builder fails");
+    }
+    if fields
+        .iter()
+        .any(|field| matches!(field.fallback, Fallback::Try(_)))
+    {
+        failures.push("a `try_default` function fails");
+    }
+    if let Some((last, rest)) = failures.split_last() {
+        let reasons = if rest.is_empty() {
+            (*last).to_owned()
+        } else {
+            format!("{} or {}", rest.join(", "), last)
+        };
+        lines.push(String::new());
+        lines.push(format!("Fails if {}.", reasons));
+    }
+    lines
+}
+
+/// Docs of the setter taking the whole value of `field`.
+pub fn setter(field: &Field) -> TokenStream {
+    if field.docs.is_empty() {
+        attrs(vec![format!("Sets `{}`.", field.ident.unraw())])
+    } else {
+        let docs = &field.docs;
+        quote!(#(#docs)*)
+    }
+}
+
+/// Docs of the `each` setter of `field`.
+pub fn each(field: &Field) -> TokenStream {
+    summarize(
+        field,
+        format!("Adds one element to `{}`.", field.ident.unraw()),
+    )
+}
+
+/// Docs of the `try_` setter of `field`.
+pub fn try_setter(field: &Field) -> TokenStream {
+    let name = field.ident.unraw();
+    summarize(
+        field,
+        format!(
+            "Sets `{}` if its validation function accepts the value, and returns its error otherwise.",
+            name,
+        ),
+    )
+}
+
+/// Docs of the `get_` method of `field`.
+pub fn getter(field: &Field) -> TokenStream {
+    summarize(
+        field,
+        format!(
+            "Returns the value of `{}`, if it has been set.",
+            field.ident.unraw(),
+        ),
+    )
+}
+
+/// Docs of the accessor to the sub-builder of `field`.
+pub fn accessor(field: &Field) -> TokenStream {
+    summarize(
+        field,
+        format!(
+            "Returns the builder of `{}`, to set its fields in place.",
+            field.ident.unraw(),
+        ),
+    )
+}
+
+/// A generated summary line followed by the doc comments of `field`.
+fn summarize(field: &Field, summary: String) -> TokenStream {
+    let summary = attrs(vec![summary]);
+    let docs = &field.docs;
+    if docs.is_empty() {
+        summary
+    } else {
+        let blank = attrs(vec![String::new()]);
+        quote!(#summary #blank #(#docs)*)
+    }
+}
+
+/// The struct or variant being built, as an intra-doc link target.
+fn target_name(builder: &Builder) -> String {
+    match builder.variant {
+        Some(variant) => format!("{}::{}", builder.target, variant),
+        None => builder.target.to_string(),
+    }
+}
+
+/// Description of the value `field` takes when it is never set.
+fn fallback(builder: &Builder, field: &Field) -> String {
+    match &field.fallback {
+        Fallback::Missing | Fallback::None => "`None`".to_owned(),
+        Fallback::Default => "`Default::default()`".to_owned(),
+        Fallback::Expr(_, source) => format!("`{}`", source),
+        Fallback::Container => format!("its value in `{}::default()`", builder.target),
+        Fallback::Async(path) => format!(
+            "`{}().await` in [`{}_async()`](Self::{1}_async), which `{}()` cannot wait for",
+            path.to_token_stream().to_string().replace(' ', ""),
+            builder.build_fn.unraw(),
+            builder.build_fn.unraw(),
+        ),
+        Fallback::Try(path) => format!(
+            "`{}()`, or its error",
+            path.to_token_stream().to_string().replace(' ', ""),
+        ),
+        Fallback::SubBuilder => format!(
+            "built by an untouched `{}`",
+            field
+                .storage_ty()
+                .to_token_stream()
+                .to_string()
+                .replace(' ', ""),
+        ),
+    }
+}
+
+/// One `#[doc]` attribute per line, as `///` comments would expand to.
+fn attrs(lines: Vec<String>) -> TokenStream {
+    let lines = lines.iter().map(|line| {
+        let line = if line.is_empty() {
+            String::new()
+        } else {
+            format!(" {}", line)
+        };
+        LitStr::new(&line, Span::call_site())
+    });
+    quote!(#(#[doc = #lines])*)
+}
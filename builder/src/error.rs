@@ -0,0 +1,301 @@
+//! The error type returned by `build()`.
+//!
+//! Every builder gets its own `{Struct}BuilderError` enum with one unit
+//! variant per field that can be missing at runtime, one `Invalid{Field}`
+//! variant per field with a validation function, one `Nested{Field}` variant
+//! per field with a sub-builder, one `Default{Field}` variant per field with a
+//! `try_default` function, plus a `Validation` variant when the struct has a
+//! validation function. A struct level `#[builder(error = "...")]` swaps
+//! the type `build()` returns for one of the caller's, converted from the
+//! generated enum through `From`.
+
+use crate::model::{Builder, Fallback, Field, Kind};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Ident, LitStr};
+
+pub fn expand(builder: &Builder, missing: &[&Field]) -> TokenStream {
+    let core = builder.core();
+    let payload = builder.error_payload();
+    let Builder {
+        vis, error_ident, ..
+    } = builder;
+
+    let variants = missing.iter().map(|field| {
+        let variant = missing_variant(field);
+        let doc = match &field.fallback {
+            Fallback::Async(_) => format!(
+                " The field `{}` was never set, and its default is only produced by `{}_async()`.",
+                field.ident.unraw(),
+                builder.build_fn.unraw(),
+            ),
+            _ => format!(
+                " The required field `{}` was never set.",
+                field.ident.unraw()
+            ),
+        };
+        let doc = LitStr::new(&doc, field.ident.span());
+        quote! {
+            #[doc = #doc]
+            #variant
+        }
+    });
+    let display = missing.iter().map(|field| {
+        let variant = missing_variant(field);
+        let msg = match &field.fallback {
+            Fallback::Async(_) => format!(
+                "field `{}` was not set and has an async default; call {}_async()",
+                field.ident.unraw(),
+                builder.build_fn.unraw(),
+            ),
+            _ => format!("missing required field `{}`", field.ident.unraw()),
+        };
+        let msg = LitStr::new(&msg, field.ident.span());
+        quote! {
+            #error_ident::#variant => #core::fmt::Formatter::write_str(__f, #msg)
+        }
+    });
+    let names = missing.iter().map(|field| {
+        let variant = missing_variant(field);
+        let name = LitStr::new(&field.ident.unraw().to_string(), field.ident.span());
+        quote! {
+            #error_ident::#variant => #core::option::Option::Some(#name)
+        }
+    });
+
+    // Variants carrying the error of a field's validation function or of its
+    // sub-builder, displayed after a message naming the field.
+    let mut wrapped: Vec<(Ident, LitStr, LitStr)> = Vec::new();
+    for field in &builder.fields {
+        let name = field.ident.unraw();
+        let mut wrap = |variant, msg: String, doc: String| {
+            wrapped.push((
+                variant,
+                LitStr::new(&msg, name.span()),
+                LitStr::new(&doc, name.span()),
+            ))
+        };
+        if field.validate.is_some() {
+            wrap(
+                invalid_variant(field),
+                format!("invalid value for field `{}`: {{}}", name),
+                format!(" The validation function of `{}` rejected its value.", name),
+            );
+        }
+        if let Kind::SubBuilder(_) = field.kind {
+            wrap(
+                nested_variant(field),
+                format!("{}: {{}}", name),
+                format!(" The sub-builder of `{}` failed.", name),
+            );
+        }
+        if let Fallback::Try(_) = field.fallback {
+            wrap(
+                default_variant(field),
+                format!("could not produce a default for field `{}`: {{}}", name),
+                format!(" The `try_default` function of `{}` failed.", name),
+            );
+        }
+    }
+    let wrapped_variants = wrapped.iter().map(|(variant, _, doc)| {
+        quote! {
+            #[doc = #doc]
+            #variant(#payload)
+        }
+    });
+    let wrapped_names = wrapped.iter().map(|(variant, _, _)| {
+        quote! {
+            #error_ident::#variant(_) => #core::option::Option::None
+        }
+    });
+    let wrapped_display = wrapped.iter().map(|(variant, msg, _)| {
+        quote! {
+            #error_ident::#variant(ref __err) => #core::write!(__f, #msg, __err)
+        }
+    });
+    let wrapped_sources = wrapped.iter().map(|(variant, _, _)| {
+        quote! {
+            #error_ident::#variant(ref __err) => #core::option::Option::Some(&**__err)
+        }
+    });
+
+    let validation = builder.attrs.validate.is_some();
+    let validation_variant = validation.then(|| {
+        quote! {
+            /// The struct's validation function rejected it.
+            Validation(#payload),
+        }
+    });
+    let validation_name = validation.then(|| {
+        quote! {
+            #error_ident::Validation(_) => #core::option::Option::None,
+        }
+    });
+    let validation_display = validation.then(|| {
+        quote! {
+            #error_ident::Validation(ref __err) => #core::fmt::Display::fmt(__err, __f),
+        }
+    });
+    let validation_source = validation.then(|| {
+        quote! {
+            #error_ident::Validation(ref __err) => #core::option::Option::Some(&**__err),
+        }
+    });
+    let other_sources = if missing.is_empty() {
+        None
+    } else {
+        Some(quote!(_ => #core::option::Option::None,))
+    };
+    // Messages carried without an allocator have no source to hand out.
+    let source = builder.has_alloc().then(|| {
+        quote! {
+            fn source(&self) -> #core::option::Option<&(dyn #core::error::Error + 'static)> {
+                match *self {
+                    #(#wrapped_sources,)*
+                    #validation_source
+                    #other_sources
+                }
+            }
+        }
+    });
+
+    let doc = LitStr::new(
+        &format!(
+            " Error returned by [`{}::{}()`].",
+            builder.ident,
+            builder.build_fn.unraw(),
+        ),
+        Span::call_site(),
+    );
+
+    quote! {
+        #[doc = #doc]
+        #[derive(#core::fmt::Debug)]
+        #vis enum #error_ident {
+            #(#variants,)*
+            #(#wrapped_variants,)*
+            #validation_variant
+        }
+
+        impl #error_ident {
+            /// The name of the field whose absence caused this error, if any.
+            pub fn missing_field(&self) -> #core::option::Option<&'static str> {
+                match *self {
+                    #(#names,)*
+                    #(#wrapped_names,)*
+                    #validation_name
+                }
+            }
+        }
+
+        impl #core::fmt::Display for #error_ident {
+            fn fmt(&self, __f: &mut #core::fmt::Formatter) -> #core::fmt::Result {
+                match *self {
+                    #(#display,)*
+                    #(#wrapped_display,)*
+                    #validation_display
+                }
+            }
+        }
+
+        impl #core::error::Error for #error_ident {
+            #source
+        }
+    }
+}
+
+/// The type in the `Err` position of `build()`'s return type.
+pub fn output(builder: &Builder) -> TokenStream {
+    match &builder.attrs.error {
+        Some(ty) => quote!(#ty),
+        None => {
+            let error_ident = &builder.error_ident;
+            quote!(#error_ident)
+        }
+    }
+}
+
+/// Statement returning the error for a missing required field out of
+/// `build()`.
+pub fn missing(builder: &Builder, field: &Field) -> TokenStream {
+    let core = builder.core();
+    let error_ident = &builder.error_ident;
+    let variant = missing_variant(field);
+    // Without a conversion the statement can be part of a `const fn`.
+    match builder.attrs.error {
+        Some(_) => quote! {
+            return #core::result::Result::Err(#core::convert::From::from(#error_ident::#variant))
+        },
+        None => quote! {
+            return #core::result::Result::Err(#error_ident::#variant)
+        },
+    }
+}
+
+/// Statement returning the error produced by the validation function, bound to
+/// `__err`, out of `build()`.
+pub fn validation(builder: &Builder) -> TokenStream {
+    let core = builder.core();
+    let error_ident = &builder.error_ident;
+    quote! {
+        return #core::result::Result::Err(#core::convert::From::from(
+            #error_ident::Validation(#core::convert::From::from(__err)),
+        ))
+    }
+}
+
+/// Statement returning the error produced by the validation function of
+/// `field`, bound to `__err`, out of a `try_` setter or `build()`.
+pub fn invalid(builder: &Builder, field: &Field) -> TokenStream {
+    let core = builder.core();
+    let error_ident = &builder.error_ident;
+    let variant = invalid_variant(field);
+    quote! {
+        return #core::result::Result::Err(#core::convert::From::from(
+            #error_ident::#variant(#core::convert::From::from(__err)),
+        ))
+    }
+}
+
+/// Statement returning the error of the sub-builder of `field`, bound to
+/// `__err`, out of `build()`.
+pub fn nested(builder: &Builder, field: &Field) -> TokenStream {
+    let core = builder.core();
+    let error_ident = &builder.error_ident;
+    let variant = nested_variant(field);
+    quote! {
+        return #core::result::Result::Err(#core::convert::From::from(
+            #error_ident::#variant(#core::convert::From::from(__err)),
+        ))
+    }
+}
+
+/// Statement returning the error of the `try_default` function of `field`,
+/// bound to `__err`, out of `build()`.
+pub fn default_failed(builder: &Builder, field: &Field) -> TokenStream {
+    let core = builder.core();
+    let error_ident = &builder.error_ident;
+    let variant = default_variant(field);
+    quote! {
+        return #core::result::Result::Err(#core::convert::From::from(
+            #error_ident::#variant(#core::convert::From::from(__err)),
+        ))
+    }
+}
+
+fn missing_variant(field: &Field) -> Ident {
+    format_ident!("Missing{}", field.camel_name())
+}
+
+fn invalid_variant(field: &Field) -> Ident {
+    format_ident!("Invalid{}", field.camel_name())
+}
+
+fn nested_variant(field: &Field) -> Ident {
+    format_ident!("Nested{}", field.camel_name())
+}
+
+fn default_variant(field: &Field) -> Ident {
+    format_ident!("Default{}", field.camel_name())
+}
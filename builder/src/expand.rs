@@ -0,0 +1,720 @@
+use crate::attr::Pattern;
+use crate::doc;
+use crate::error;
+use crate::model::{Builder, Fallback, Field, Kind};
+use crate::ty::{self, Item};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, quote_spanned};
+use syn::ext::IdentExt;
+use syn::spanned::Spanned;
+use syn::LitStr;
+
+pub fn expand(builder: &Builder) -> TokenStream {
+    let core = builder.core();
+    let Builder {
+        vis,
+        target,
+        ctor,
+        constructor,
+        ident: builder_ident,
+        build_fn,
+        generics,
+        fields,
+        ..
+    } = builder;
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    let stored = || fields.iter().filter(|field| field.is_stored());
+    let storage = stored().map(|field| {
+        let ident = &field.ident;
+        let ty = field.storage_ty();
+        quote!(#ident: #core::option::Option<#ty>)
+    });
+    let init = stored().map(|field| {
+        let ident = &field.ident;
+        quote!(#ident: #core::option::Option::None)
+    });
+    let setters = fields.iter().map(|field| setters(builder, field));
+    let container_default = container_default(builder);
+    let build_fields = fields
+        .iter()
+        .map(|field| build_field(builder, field, false));
+    let validate_fields = validate_fields(builder, fields);
+    let validate = validate(builder);
+    let missing: Vec<&Field> = fields
+        .iter()
+        .filter(|field| field.can_be_missing())
+        .collect();
+    let error = error::expand(builder, &missing);
+    let error_ty = error::output(builder);
+    let target_ty = builder.target_ty();
+
+    // Unless the builder is consumed, fields are cloned out of it, which needs
+    // spelling out for the ones whose type depends on a type parameter.
+    let pattern = builder.pattern();
+    let constness = if is_const_build(builder) {
+        Some(quote!(const))
+    } else {
+        None
+    };
+    let build_receiver = match pattern {
+        Pattern::Owned => quote!(self),
+        Pattern::Mutable | Pattern::Immutable => quote!(&self),
+    };
+    let clone_bounds: Vec<TokenStream> = stored()
+        .filter(|field| !matches!(field.kind, Kind::SubBuilder(_)))
+        .filter(|field| pattern != Pattern::Owned && builder.is_generic(field.ty))
+        .map(|field| {
+            let ty = field.ty;
+            quote!(#ty: #core::clone::Clone)
+        })
+        .collect();
+    let derive_clone = if pattern == Pattern::Immutable {
+        Some(quote!(#[derive(#core::clone::Clone)]))
+    } else {
+        None
+    };
+    let (derive_deserialize, skip_marker) = if builder.attrs.serde {
+        let serde = builder.serde();
+        let serde_crate = LitStr::new(&serde.to_string().replace(' ', ""), Span::call_site());
+        (
+            Some(quote! {
+                #[derive(#serde::Deserialize)]
+                #[serde(crate = #serde_crate)]
+            }),
+            Some(quote!(#[serde(skip)])),
+        )
+    } else {
+        (None, None)
+    };
+    let builder_doc = doc::builder(builder);
+    let constructor_doc = doc::constructor(builder);
+    let build_doc = doc::build_fn(builder);
+    let getters = getters(builder);
+    let merge = merge(builder);
+    let nested_build = nested_build(builder, &clone_bounds);
+
+    // The same as build(), except that defaults given by async functions are
+    // awaited rather than missing.
+    let build_async = builder.is_async().then(|| {
+        let build_async = format_ident!("{}_async", build_fn.unraw());
+        let build_fields = fields.iter().map(|field| build_field(builder, field, true));
+        let doc = doc::build_async(builder);
+        quote! {
+            #doc
+            pub async fn #build_async(#build_receiver) -> #core::result::Result<#target_ty, #error_ty>
+            where
+                #(#clone_bounds,)*
+            {
+                #validate_fields
+                #container_default
+                let __value = #ctor {
+                    #(#build_fields,)*
+                };
+                #validate
+                #core::result::Result::Ok(__value)
+            }
+        }
+    });
+
+    quote! {
+        #builder_doc
+        #derive_clone
+        #derive_deserialize
+        #vis struct #builder_ident #generics #where_clause {
+            #(#storage,)*
+            #skip_marker
+            __marker: #core::marker::PhantomData<fn() -> #target_ty>,
+        }
+
+        impl #impl_generics #target #ty_generics #where_clause {
+            #constructor_doc
+            #vis const fn #constructor() -> #builder_ident #ty_generics {
+                #builder_ident {
+                    #(#init,)*
+                    __marker: #core::marker::PhantomData,
+                }
+            }
+        }
+
+        impl #impl_generics #core::default::Default for #builder_ident #ty_generics #where_clause {
+            fn default() -> Self {
+                #target::#constructor()
+            }
+        }
+
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #(#setters)*
+
+            #getters
+
+            #merge
+
+            #build_doc
+            pub #constness fn #build_fn(#build_receiver) -> #core::result::Result<#target_ty, #error_ty>
+            where
+                #(#clone_bounds,)*
+            {
+                #validate_fields
+                #container_default
+                let __value = #ctor {
+                    #(#build_fields,)*
+                };
+                #validate
+                #core::result::Result::Ok(__value)
+            }
+
+            #build_async
+
+            #nested_build
+        }
+
+        #error
+    }
+}
+
+pub fn setters(builder: &Builder, field: &Field) -> TokenStream {
+    let core = builder.core();
+    let Field {
+        ident, ty, kind, ..
+    } = field;
+
+    // The receiver and return type of a setter, statements run first, and the
+    // expression for the builder being modified and returned.
+    let (receiver, output, setup, this) = match builder.pattern() {
+        Pattern::Owned => (quote!(mut self), quote!(Self), None, quote!(self)),
+        Pattern::Mutable => (quote!(&mut self), quote!(&mut Self), None, quote!(self)),
+        Pattern::Immutable => (
+            quote!(&self),
+            quote!(Self),
+            Some(quote!(let mut __builder = #core::clone::Clone::clone(self);)),
+            quote!(__builder),
+        ),
+    };
+
+    if let Kind::SubBuilder(builder_ty) = kind {
+        let doc = doc::accessor(field);
+        // Fails at the field for a builder which cannot be nested.
+        let check = quote_spanned!(ty.span()=> <#builder_ty>::__sub_builder(););
+        return quote! {
+            #doc
+            pub fn #ident(&mut self) -> &mut #builder_ty {
+                #check
+                self.#ident.get_or_insert_with(#core::default::Default::default)
+            }
+        };
+    }
+
+    let each = match kind {
+        Kind::Each { setter, item } => {
+            // Parameters of the setter and the element they make up.
+            let (generics, params, element, bound) = match item {
+                Item::Single(item) => {
+                    let (arg, value) = field.setter_input(&core, setter, item);
+                    (None, quote!(#setter: #arg), value, None)
+                }
+                Item::Pair(key_ty, value_ty) => {
+                    let (key_arg, key) = field.setter_input(&core, &format_ident!("__key"), key_ty);
+                    let (value_arg, value) =
+                        field.setter_input(&core, &format_ident!("__value"), value_ty);
+                    (
+                        None,
+                        quote!(__key: #key_arg, __value: #value_arg),
+                        quote!((#key, #value)),
+                        None,
+                    )
+                }
+                Item::Unknown => (
+                    Some(quote!(<__Item>)),
+                    quote!(#setter: __Item),
+                    quote!(#setter),
+                    Some(quote!(where #ty: #core::iter::Extend<__Item>)),
+                ),
+            };
+            let doc = doc::each(field);
+            Some(quote! {
+                #doc
+                pub fn #setter #generics(#receiver, #params) -> #output #bound {
+                    #setup
+                    #core::iter::Extend::extend(
+                        #this.#ident.get_or_insert_with(#core::default::Default::default),
+                        #core::iter::once(#element),
+                    );
+                    #this
+                }
+            })
+        }
+        _ => None,
+    };
+
+    let constness = if is_const_setter(builder, field) {
+        Some(quote!(const))
+    } else {
+        None
+    };
+    let all_at_once = if field.has_all_at_once_setter() {
+        let (arg, value) = match kind {
+            Kind::Optional(inner) => {
+                let (arg, value) = field.setter_input(&core, ident, inner);
+                (arg, quote!(#core::option::Option::Some(#value)))
+            }
+            _ => field.setter_input(&core, ident, ty),
+        };
+        let doc = doc::setter(field);
+        Some(quote! {
+            #doc
+            pub #constness fn #ident(#receiver, #ident: #arg) -> #output {
+                #setup
+                #this.#ident = #core::option::Option::Some(#value);
+                #this
+            }
+        })
+    } else {
+        None
+    };
+
+    let try_setter = try_setter(builder, field, receiver_by_ref(builder), output);
+
+    quote! {
+        #all_at_once
+        #try_setter
+        #each
+    }
+}
+
+/// Methods through which an enclosing builder builds this one as a
+/// sub-builder, whatever the name of `build()` and the way it takes the
+/// builder. `__build_owned` consumes the builder and `__build_ref` borrows it,
+/// cloning the fields of a builder whose `build()` consumes it. `__merge` is
+/// `merge()`, which the enclosing builder's `merge()` calls whether or not
+/// this one asked for it. `__sub_builder` does nothing; the one of a
+/// typestate builder is what reports that it cannot be nested.
+fn nested_build(builder: &Builder, clone_bounds: &[TokenStream]) -> TokenStream {
+    let core = builder.core();
+    let build_fn = &builder.build_fn;
+    let builder_ident = &builder.ident;
+    let target_ty = builder.target_ty();
+    let error_ty = error::output(builder);
+    let output = quote!(#core::result::Result<#target_ty, #error_ty>);
+
+    let build_ref = match builder.pattern() {
+        Pattern::Owned => {
+            let stored = || builder.fields.iter().filter(|field| field.is_stored());
+            let idents = stored().map(|field| &field.ident);
+            // Bound through a lifetime, as in to_builder(), so that builders
+            // of fields which cannot be cloned are only rejected when used.
+            let bounds = stored().map(|field| {
+                let ty = field.storage_ty();
+                quote!(for<'__a> #core::option::Option<#ty>: #core::clone::Clone)
+            });
+            quote! {
+                #[doc(hidden)]
+                pub fn __build_ref(&self) -> #output
+                where
+                    #(#bounds,)*
+                {
+                    #builder_ident {
+                        #(#idents: #core::clone::Clone::clone(&self.#idents),)*
+                        __marker: #core::marker::PhantomData,
+                    }
+                    .#build_fn()
+                }
+            }
+        }
+        Pattern::Mutable | Pattern::Immutable => quote! {
+            #[doc(hidden)]
+            pub fn __build_ref(&self) -> #output
+            where
+                #(#clone_bounds,)*
+            {
+                self.#build_fn()
+            }
+        },
+    };
+    let owned_bounds = match builder.pattern() {
+        Pattern::Owned => None,
+        Pattern::Mutable | Pattern::Immutable => Some(clone_bounds),
+    };
+    let owned_bounds = owned_bounds.into_iter().flatten();
+
+    let stored: Vec<&Field> = builder
+        .fields
+        .iter()
+        .filter(|field| field.is_stored())
+        .collect();
+    let idents = stored.iter().map(|field| &field.ident);
+    let merged = stored.iter().map(|field| {
+        let ident = &field.ident;
+        match field.kind {
+            Kind::SubBuilder(_) => quote! {
+                if let #core::option::Option::Some(__value) = #ident {
+                    match &mut self.#ident {
+                        #core::option::Option::Some(__builder) => __builder.__merge(__value),
+                        #core::option::Option::None => {
+                            self.#ident = #core::option::Option::Some(__value);
+                        }
+                    }
+                }
+            },
+            _ => quote! {
+                if let #core::option::Option::Some(__value) = #ident {
+                    self.#ident = #core::option::Option::Some(__value);
+                }
+            },
+        }
+    });
+
+    quote! {
+        #[doc(hidden)]
+        pub fn __sub_builder() {}
+
+        #[doc(hidden)]
+        pub fn __build_owned(self) -> #output
+        where
+            #(#owned_bounds,)*
+        {
+            self.#build_fn()
+        }
+
+        #build_ref
+
+        #[doc(hidden)]
+        pub fn __merge(&mut self, __other: Self) {
+            let #builder_ident {
+                #(#idents,)*
+                __marker: _,
+            } = __other;
+            #(#merged)*
+        }
+    }
+}
+
+/// With `#[builder(merge)]`, `merge()` taking the fields set on another
+/// builder.
+fn merge(builder: &Builder) -> Option<TokenStream> {
+    if !builder.attrs.merge {
+        return None;
+    }
+    Some(quote! {
+        /// Takes every field set on the given builder, keeping the value
+        /// already set on `self` for the fields it leaves unset. Sub-builders
+        /// set on both are merged in turn.
+        pub fn merge(&mut self, __other: Self) {
+            self.__merge(__other)
+        }
+    })
+}
+
+/// With `#[builder(getters)]`, a `get_` method per field returning what it
+/// was set to, and `is_complete()` and `missing_fields()` checking the fields
+/// which `build()` reports as missing.
+fn getters(builder: &Builder) -> Option<TokenStream> {
+    if !builder.attrs.getters {
+        return None;
+    }
+    let core = builder.core();
+
+    let getters = builder
+        .fields
+        .iter()
+        .filter(|field| field.is_stored())
+        .map(|field| {
+            let ident = &field.ident;
+            let getter = format_ident!("get_{}", ident.unraw());
+            let doc = doc::getter(field);
+            // The setter of an optional field always stores `Some`, so there
+            // is nothing lost in flattening the two options.
+            let (ty, value) = match field.kind {
+                Kind::Optional(inner) => (
+                    inner,
+                    quote! {
+                        match &self.#ident {
+                            #core::option::Option::Some(__value) => #core::option::Option::as_ref(__value),
+                            #core::option::Option::None => #core::option::Option::None,
+                        }
+                    },
+                ),
+                _ => (
+                    field.storage_ty(),
+                    quote!(#core::option::Option::as_ref(&self.#ident)),
+                ),
+            };
+            quote! {
+                #doc
+                pub fn #getter(&self) -> #core::option::Option<&#ty> {
+                    #value
+                }
+            }
+        });
+
+    let needed: Vec<&Field> = builder
+        .fields
+        .iter()
+        .filter(|field| field.can_be_missing())
+        .collect();
+    let needed_idents = needed.iter().map(|field| &field.ident);
+    let missing_fields = builder.has_alloc().then(|| {
+        let alloc = builder.alloc();
+        let idents = needed.iter().map(|field| &field.ident);
+        let names = needed
+            .iter()
+            .map(|field| LitStr::new(&field.ident.unraw().to_string(), field.ident.span()));
+        let body = if needed.is_empty() {
+            quote!(#alloc::vec::Vec::new())
+        } else {
+            quote! {
+                let mut __missing = #alloc::vec::Vec::new();
+                #(
+                    if #core::option::Option::is_none(&self.#idents) {
+                        __missing.push(#names);
+                    }
+                )*
+                __missing
+            }
+        };
+        quote! {
+            /// The names of the fields which `build()` needs and which have not
+            /// been set yet, in the order the struct declares them. Fields
+            /// with a `default_with` function count, as only the async build
+            /// can produce their default.
+            pub fn missing_fields(&self) -> #alloc::vec::Vec<&'static str> {
+                #body
+            }
+        }
+    });
+
+    Some(quote! {
+        #(#getters)*
+
+        /// Whether every field `build()` needs has been set, including those
+        /// with a `default_with` function. Validation functions and
+        /// sub-builders only run in `build()`, which can still fail because
+        /// of them.
+        pub fn is_complete(&self) -> bool {
+            true #(&& #core::option::Option::is_some(&self.#needed_idents))*
+        }
+
+        #missing_fields
+    })
+}
+
+/// Whether the plain setter of `field` can be a `const fn`: it must store
+/// the value as given, and overwriting a previous value must not run a
+/// destructor.
+fn is_const_setter(builder: &Builder, field: &Field) -> bool {
+    builder.pattern() != Pattern::Immutable
+        && !field.into
+        && matches!(field.kind, Kind::Plain | Kind::Optional(_))
+        && ty::is_trivial(field.ty)
+}
+
+/// Whether `build()` can be a `const fn`: every field must be copied or moved
+/// out of the builder as it is or be missing, with no conversion or
+/// validation in between.
+fn is_const_build(builder: &Builder) -> bool {
+    builder.pattern() != Pattern::Immutable
+        && builder.attrs.validate.is_none()
+        && builder.attrs.error.is_none()
+        && builder.fields.iter().all(|field| {
+            matches!(field.kind, Kind::Plain | Kind::Optional(_))
+                && matches!(field.fallback, Fallback::Missing | Fallback::None)
+                && field.validate.is_none()
+                && ty::is_trivial(field.ty)
+        })
+}
+
+/// The receiver of a `try_` setter, which leaves cloning or modifying the
+/// builder to the plain setter it calls.
+fn receiver_by_ref(builder: &Builder) -> TokenStream {
+    match builder.pattern() {
+        Pattern::Owned => quote!(self),
+        Pattern::Mutable => quote!(&mut self),
+        Pattern::Immutable => quote!(&self),
+    }
+}
+
+/// The `try_` setter of a field with `try_setter`, which runs the field's
+/// validation function before passing the value on to the plain setter and
+/// returning what it returns.
+pub fn try_setter(
+    builder: &Builder,
+    field: &Field,
+    receiver: TokenStream,
+    output: TokenStream,
+) -> Option<TokenStream> {
+    let core = builder.core();
+    let path = field.validate.as_ref().filter(|_| field.try_setter)?;
+    let ident = &field.ident;
+    let try_ident = format_ident!("try_{}", ident.unraw());
+    let ty = field.setter_ty();
+    let (arg, value) = field.setter_input(&core, ident, ty);
+    let error_ty = error::output(builder);
+    let invalid = error::invalid(builder, field);
+    let doc = doc::try_setter(field);
+    Some(quote! {
+        #doc
+        pub fn #try_ident(#receiver, #ident: #arg) -> #core::result::Result<#output, #error_ty> {
+            let #ident: #ty = #value;
+            if let #core::result::Result::Err(__err) = #path(&#ident) {
+                #invalid;
+            }
+            #core::result::Result::Ok(self.#ident(#ident))
+        }
+    })
+}
+
+/// Runs the validation function of each of `fields`, on the value it was set
+/// to if any, returning early with the first error.
+pub fn validate_fields<'a, I>(builder: &Builder, fields: I) -> TokenStream
+where
+    I: IntoIterator<Item = &'a Field<'a>>,
+{
+    let core = builder.core();
+    let checks = fields.into_iter().filter_map(|field| {
+        let path = field.validate.as_ref()?;
+        let ident = &field.ident;
+        let pat = match field.kind {
+            Kind::Optional(_) => quote! {
+                #core::option::Option::Some(#core::option::Option::Some(__value))
+            },
+            _ => quote!(#core::option::Option::Some(__value)),
+        };
+        let invalid = error::invalid(builder, field);
+        Some(quote! {
+            if let #pat = &self.#ident {
+                if let #core::result::Result::Err(__err) = #path(__value) {
+                    #invalid;
+                }
+            }
+        })
+    });
+    quote!(#(#checks)*)
+}
+
+/// Initializer of `field` in `build()`, or in `build_async()` if
+/// `asynchronous`.
+pub fn build_field(builder: &Builder, field: &Field, asynchronous: bool) -> TokenStream {
+    let core = builder.core();
+    let Field { ident, member, .. } = field;
+    let fallback = fallback(builder, field, asynchronous);
+
+    if !field.is_stored() {
+        return quote!(#member: #fallback);
+    }
+
+    if let Kind::SubBuilder(_) = field.kind {
+        let borrow = match builder.pattern() {
+            Pattern::Owned => None,
+            Pattern::Mutable | Pattern::Immutable => Some(quote!(&)),
+        };
+        let built = match builder.pattern() {
+            Pattern::Owned => {
+                build_sub_builder(builder, field, quote!(__sub_builder.__build_owned()))
+            }
+            Pattern::Mutable | Pattern::Immutable => {
+                build_sub_builder(builder, field, quote!(__sub_builder.__build_ref()))
+            }
+        };
+        return quote! {
+            #member: match #borrow self.#ident {
+                #core::option::Option::Some(__sub_builder) => #built,
+                #core::option::Option::None => #fallback,
+            }
+        };
+    }
+
+    match builder.pattern() {
+        Pattern::Owned => quote! {
+            #member: match self.#ident {
+                #core::option::Option::Some(__value) => __value,
+                #core::option::Option::None => #fallback,
+            }
+        },
+        Pattern::Mutable | Pattern::Immutable if is_const_build(builder) => quote! {
+            #member: match &self.#ident {
+                #core::option::Option::Some(__value) => *__value,
+                #core::option::Option::None => #fallback,
+            }
+        },
+        Pattern::Mutable | Pattern::Immutable => quote! {
+            #member: match &self.#ident {
+                #core::option::Option::Some(__value) => #core::clone::Clone::clone(__value),
+                #core::option::Option::None => #fallback,
+            }
+        },
+    }
+}
+
+/// Expression giving the value of a sub-builder field out of the result of
+/// `build`, returning early with its error.
+fn build_sub_builder(builder: &Builder, field: &Field, build: TokenStream) -> TokenStream {
+    let core = builder.core();
+    let nested = error::nested(builder, field);
+    quote! {
+        match #build {
+            #core::result::Result::Ok(__value) => __value,
+            #core::result::Result::Err(__err) => #nested,
+        }
+    }
+}
+
+/// Binds `__default` to the struct's own `Default` value if any field falls
+/// back to it.
+pub fn container_default(builder: &Builder) -> Option<TokenStream> {
+    let core = builder.core();
+    if builder.uses_container_default() {
+        let target_ty = builder.target_ty();
+        Some(quote! {
+            let __default: #target_ty = #core::default::Default::default();
+        })
+    } else {
+        None
+    }
+}
+
+/// Passes the assembled struct, bound to `__value`, through the user's
+/// validation function and returns early with its error.
+pub fn validate(builder: &Builder) -> Option<TokenStream> {
+    let core = builder.core();
+    let path = builder.attrs.validate.as_ref()?;
+    let validation = error::validation(builder);
+    Some(quote! {
+        if let #core::result::Result::Err(__err) = #path(&__value) {
+            #validation;
+        }
+    })
+}
+
+/// Expression producing the value of a field that was never set.
+fn fallback(builder: &Builder, field: &Field, asynchronous: bool) -> TokenStream {
+    let core = builder.core();
+    match &field.fallback {
+        Fallback::Missing => error::missing(builder, field),
+        Fallback::None => quote!(#core::option::Option::None),
+        Fallback::Default => quote!(#core::default::Default::default()),
+        Fallback::Expr(expr, _) => quote!(#expr),
+        Fallback::Container => {
+            let member = &field.member;
+            quote!(__default.#member)
+        }
+        Fallback::Async(path) if asynchronous => quote!(#path().await),
+        Fallback::Async(_) => error::missing(builder, field),
+        Fallback::Try(path) => {
+            let failed = error::default_failed(builder, field);
+            quote! {
+                match #path() {
+                    #core::result::Result::Ok(__value) => __value,
+                    #core::result::Result::Err(__err) => #failed,
+                }
+            }
+        }
+        Fallback::SubBuilder => {
+            let builder_ty = field.storage_ty();
+            build_sub_builder(
+                builder,
+                field,
+                quote!(<#builder_ty as #core::default::Default>::default().__build_owned()),
+            )
+        }
+    }
+}
@@ -1,8 +1,38 @@
+mod attr;
+mod convert;
+mod doc;
+mod error;
+mod expand;
+mod model;
+mod ty;
+mod typestate;
+
 use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, DeriveInput};
 
-#[proc_macro_derive(Builder)]
+#[proc_macro_derive(Builder, attributes(builder))]
 pub fn derive(input: TokenStream) -> TokenStream {
-    let _ = input;
+    let input = parse_macro_input!(input as DeriveInput);
 
-    unimplemented!()
+    model::Builder::from_input(&input)
+        .map(|builders| {
+            builders
+                .iter()
+                .map(|builder| {
+                    let expanded = if builder.attrs.typestate {
+                        typestate::expand(builder)
+                    } else {
+                        expand::expand(builder)
+                    };
+                    let convert = convert::expand(builder);
+                    quote! {
+                        #expanded
+                        #convert
+                    }
+                })
+                .collect()
+        })
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
 }
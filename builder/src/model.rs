@@ -0,0 +1,648 @@
+use crate::attr::{self, ContainerAttrs, DefaultValue, Errors, Pattern};
+use crate::ty;
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote, ToTokens};
+use syn::ext::IdentExt;
+use syn::{
+    Attribute, Data, DeriveInput, Error, Expr, Fields, GenericParam, Generics, Ident, Index,
+    Member, Path, Result, Type, Visibility,
+};
+
+/// Everything the expansion needs to know about one builder: the builder of
+/// a struct, or of one variant of an enum.
+pub struct Builder<'a> {
+    /// Visibility of the builder, its constructor and its error type.
+    pub vis: Visibility,
+    /// The struct or enum being built.
+    pub target: &'a Ident,
+    /// The variant built, for the builder of one variant of an enum.
+    pub variant: Option<&'a Ident>,
+    /// Path which constructs a value in `build()`: the struct's name, or
+    /// `Enum::Variant`.
+    pub ctor: TokenStream,
+    /// Associated function of the target which returns a new builder.
+    pub constructor: Ident,
+    pub ident: Ident,
+    pub error_ident: Ident,
+    /// Name of the method which builds the value.
+    pub build_fn: Ident,
+    pub generics: &'a Generics,
+    pub attrs: ContainerAttrs,
+    pub fields: Vec<Field<'a>>,
+}
+
+pub struct Field<'a> {
+    /// Name of the field's setter and of its storage in the builder.
+    pub ident: Ident,
+    /// How the built value refers to the field: by name, or by position in a
+    /// tuple struct or variant.
+    pub member: Member,
+    pub ty: &'a Type,
+    /// The field's doc comments, passed on to its setters.
+    pub docs: Vec<&'a Attribute>,
+    pub kind: Kind<'a>,
+    pub fallback: Fallback,
+    /// Setters accept `impl Into<T>` rather than `T`.
+    pub into: bool,
+    /// Function checking the value given to the field's setter, run by its
+    /// `try_` setter and again by `build()`.
+    pub validate: Option<Path>,
+    /// Generate a `try_` setter alongside the plain one.
+    pub try_setter: bool,
+}
+
+/// Shape of the setters generated for a field.
+pub enum Kind<'a> {
+    /// The setter takes the field's type.
+    Plain,
+    /// `Option<T>`; the setter takes `T`.
+    Optional(&'a Type),
+    /// A collection built up one element at a time by a setter called
+    /// `setter`.
+    Each { setter: Ident, item: ty::Item<'a> },
+    /// No setter and no storage in the builder; the field is always built
+    /// from its fallback.
+    Skip,
+    /// Built by a nested builder of the given type, which the builder stores
+    /// in place of the field's value and hands out through an accessor.
+    SubBuilder(Type),
+}
+
+/// What `build()` does with a field that was never set.
+pub enum Fallback {
+    /// Report the field as missing.
+    Missing,
+    /// `None`, for `Option<T>` fields.
+    None,
+    /// `Default::default()`, for `each` fields and `#[builder(default)]`.
+    Default,
+    /// `#[builder(default = "expr")]`, along with the expression as written
+    /// for the builder's documentation.
+    Expr(Expr, String),
+    /// The field's value in the struct's own `Default` impl, for a struct
+    /// level `#[builder(default)]`.
+    Container,
+    /// Build an untouched sub-builder, so that the nested struct's own
+    /// defaults apply.
+    SubBuilder,
+    /// `#[builder(default_with = "path")]`: await `path()` in
+    /// `build_async()`, and report the field as missing in `build()`.
+    Async(Path),
+    /// `#[builder(try_default = "path")]`: call `path()` and return its error.
+    Try(Path),
+}
+
+impl<'a> Builder<'a> {
+    /// Builders for a struct, or one for each variant of an enum.
+    pub fn from_input(input: &'a DeriveInput) -> Result<Vec<Self>> {
+        let errors = Errors::default();
+        let builders = Self::from_input_with(input, &errors);
+        errors.finish(builders)
+    }
+
+    /// Builders for `input`, recording the mistakes in its attributes to
+    /// `errors` rather than stopping at the first.
+    fn from_input_with(input: &'a DeriveInput, errors: &Errors) -> Result<Vec<Self>> {
+        let attrs = attr::container(&input.attrs, errors);
+        let target = &input.ident;
+
+        match &input.data {
+            Data::Struct(data) => {
+                let constructor = attrs
+                    .constructor
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("builder"));
+                let ident = attrs
+                    .name
+                    .clone()
+                    .unwrap_or_else(|| format_ident!("{}Builder", target));
+                Ok(vec![Builder::new(
+                    input,
+                    attrs,
+                    None,
+                    constructor,
+                    ident,
+                    &data.fields,
+                    errors,
+                )?])
+            }
+            Data::Enum(data) => {
+                // Each variant gets its own builder, so there is no single
+                // name to give them.
+                let unsupported = if attrs.default {
+                    Some("default")
+                } else if attrs.name.is_some() {
+                    Some("name = \"...\"")
+                } else if attrs.constructor.is_some() {
+                    Some("constructor = \"...\"")
+                } else {
+                    None
+                };
+                if let Some(option) = unsupported {
+                    errors.push(Error::new(
+                        Span::call_site(),
+                        format!("`#[builder({})]` is not supported on enums", option),
+                    ));
+                }
+                // A variant builder cannot hold any other variant, so there is
+                // no converting an enum back into one.
+                if let Some(span) = attrs.to_builder_span {
+                    errors.push(Error::new(
+                        span,
+                        "`#[builder(to_builder = ...)]` is not supported on enums",
+                    ));
+                }
+                data.variants
+                    .iter()
+                    .map(|variant| {
+                        if let Some(attr) = variant
+                            .attrs
+                            .iter()
+                            .find(|attr| attr.path().is_ident("builder"))
+                        {
+                            errors.push(Error::new_spanned(
+                                attr,
+                                "`#[builder]` is not supported on enum variants",
+                            ));
+                        }
+                        let variant_ident = &variant.ident;
+                        Builder::new(
+                            input,
+                            attrs.clone(),
+                            Some(variant_ident),
+                            format_ident!(
+                                "{}_builder",
+                                snake_case(&variant_ident.unraw().to_string())
+                            ),
+                            format_ident!("{}{}Builder", target, variant_ident),
+                            &variant.fields,
+                            errors,
+                        )
+                    })
+                    .collect()
+            }
+            Data::Union(_) => Err(Error::new(
+                Span::call_site(),
+                "derive(Builder) does not support unions",
+            )),
+        }
+    }
+
+    fn new(
+        input: &'a DeriveInput,
+        attrs: ContainerAttrs,
+        variant: Option<&'a Ident>,
+        constructor: Ident,
+        ident: Ident,
+        input_fields: &'a Fields,
+        errors: &Errors,
+    ) -> Result<Self> {
+        let mut fields = Vec::new();
+        for (i, field) in input_fields.iter().enumerate() {
+            let field_attrs = attr::field(&field.attrs, errors);
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            let field_ident = match (field_attrs.name, &field.ident) {
+                (Some(name), _) => name,
+                (None, Some(ident)) => ident.clone(),
+                (None, None) => format_ident!("_{}", i),
+            };
+            let option = ty::unwrap(&field.ty, "Option");
+            let strip_option = field_attrs
+                .strip_option
+                .as_ref()
+                .is_none_or(|strip| strip.value);
+            let kind = if let Some(builder_ty) = field_attrs.sub_builder {
+                if field_attrs.each.is_some() || field_attrs.skip || field_attrs.validate.is_some()
+                {
+                    errors.push(Error::new_spanned(
+                        field,
+                        "`sub_builder` cannot be combined with `each`, `setter(skip)` or `validate`",
+                    ));
+                    continue;
+                }
+                if attrs.no_std && !attrs.alloc {
+                    errors.push(Error::new_spanned(
+                        field,
+                        "`sub_builder` in a `no_std` builder needs `builder(alloc)` to carry the nested builder's error",
+                    ));
+                    continue;
+                }
+                Kind::SubBuilder(
+                    match builder_ty.map_or_else(|| ty::builder_of(&field.ty), Ok) {
+                        Ok(builder_ty) => builder_ty,
+                        Err(error) => {
+                            errors.push(error);
+                            continue;
+                        }
+                    },
+                )
+            } else {
+                match field_attrs.each {
+                    Some(setter) if field_attrs.skip => {
+                        errors.push(Error::new_spanned(
+                            setter,
+                            "`each` cannot be combined with `setter(skip)`",
+                        ));
+                        continue;
+                    }
+                    None if field_attrs.skip => Kind::Skip,
+                    Some(setter) => Kind::Each {
+                        setter,
+                        item: ty::collection_item(&field.ty),
+                    },
+                    None => match option {
+                        Some(inner) if strip_option => Kind::Optional(inner),
+                        _ => Kind::Plain,
+                    },
+                }
+            };
+            let has_setter = !matches!(kind, Kind::Skip | Kind::SubBuilder(_));
+            if let (None, Some(strip), true) = (option, &field_attrs.strip_option, has_setter) {
+                errors.push(Error::new_spanned(
+                    strip,
+                    "`setter(strip_option)` only applies to fields of type `Option<T>`",
+                ));
+            }
+            match (&kind, &field_attrs.validate) {
+                (Kind::Each { .. }, Some(validate)) | (Kind::Skip, Some(validate)) => {
+                    errors.push(Error::new_spanned(
+                        validate,
+                        "`validate` cannot be combined with `each` or `setter(skip)`",
+                    ));
+                    continue;
+                }
+                (_, None) if field_attrs.try_setter => {
+                    errors.push(Error::new_spanned(
+                        field,
+                        "`try_setter` needs a validation function, given with `validate = \"...\"`",
+                    ));
+                    continue;
+                }
+                _ => {}
+            }
+            let fallback = match field_attrs.default {
+                Some(DefaultValue::Trait) => Fallback::Default,
+                Some(DefaultValue::Expr(expr, source)) => Fallback::Expr(expr, source),
+                Some(DefaultValue::With(path)) => {
+                    if attrs.typestate {
+                        errors.push(Error::new_spanned(
+                            path,
+                            "`default_with` is not supported by a typestate builder",
+                        ));
+                        continue;
+                    }
+                    Fallback::Async(path)
+                }
+                Some(DefaultValue::Try(path)) => Fallback::Try(path),
+                None if attrs.default => Fallback::Container,
+                None => match kind {
+                    Kind::SubBuilder(_) => Fallback::SubBuilder,
+                    _ if option.is_some() => Fallback::None,
+                    Kind::Plain => Fallback::Missing,
+                    Kind::Optional(_) => Fallback::None,
+                    Kind::Each { .. } | Kind::Skip => Fallback::Default,
+                },
+            };
+            fields.push(Field {
+                ident: field_ident,
+                member,
+                ty: &field.ty,
+                docs: field
+                    .attrs
+                    .iter()
+                    .filter(|attr| attr.path().is_ident("doc"))
+                    .collect(),
+                kind,
+                fallback,
+                into: field_attrs.setter_into || attrs.setter_into,
+                validate: field_attrs.validate,
+                try_setter: field_attrs.try_setter,
+            });
+        }
+
+        let target = &input.ident;
+        let builder = Builder {
+            vis: attrs.vis.clone().unwrap_or_else(|| input.vis.clone()),
+            target,
+            variant,
+            ctor: match variant {
+                Some(variant) => quote!(#target::#variant),
+                None => quote!(#target),
+            },
+            constructor,
+            error_ident: format_ident!("{}Error", ident),
+            ident,
+            build_fn: attrs
+                .build_fn
+                .clone()
+                .unwrap_or_else(|| format_ident!("build")),
+            generics: &input.generics,
+            attrs,
+            fields,
+        };
+        builder.check_method_names(errors);
+        if let Some(error) = &builder.attrs.error {
+            if builder.attrs.typestate && !builder.is_fallible() {
+                errors.push(Error::new_spanned(
+                    error,
+                    "`builder(error = \"...\")` has no effect on a typestate builder which cannot fail, whose build() returns the struct itself",
+                ));
+            }
+        }
+        Ok(builder)
+    }
+
+    /// Reports every setter named the same as another setter or as one of
+    /// the other methods generated on the builder, at the field it belongs
+    /// to, rather than leaving the compiler to find two definitions of it.
+    fn check_method_names(&self, errors: &Errors) {
+        // Each setter's name, where to report it, and the field it sets.
+        let mut setters: Vec<(String, &Ident, &Ident)> = Vec::new();
+        for field in &self.fields {
+            let ident = &field.ident;
+            match &field.kind {
+                Kind::Skip => {}
+                Kind::Each { setter, .. } => {
+                    if field.has_all_at_once_setter() {
+                        setters.push((ident.unraw().to_string(), ident, ident));
+                    }
+                    setters.push((setter.unraw().to_string(), setter, ident));
+                }
+                _ => setters.push((ident.unraw().to_string(), ident, ident)),
+            }
+            if field.try_setter && field.validate.is_some() {
+                setters.push((format!("try_{}", ident.unraw()), ident, ident));
+            }
+        }
+
+        let build_fn = self.build_fn.unraw().to_string();
+        let mut methods: Vec<(String, &str)> =
+            vec![(build_fn.clone(), "the method which builds the value")];
+        if self.is_async() {
+            methods.push((
+                format!("{}_async", build_fn),
+                "the method which awaits the `default_with` functions",
+            ));
+        }
+        if self.attrs.merge {
+            methods.push((
+                "merge".to_owned(),
+                "the method generated by `builder(merge)`",
+            ));
+        }
+        if self.attrs.getters {
+            let getters = self.fields.iter().filter(|field| field.is_stored());
+            methods.extend(getters.map(|field| {
+                (
+                    format!("get_{}", field.ident.unraw()),
+                    "a method generated by `builder(getters)`",
+                )
+            }));
+            methods.push((
+                "is_complete".to_owned(),
+                "a method generated by `builder(getters)`",
+            ));
+            if self.has_alloc() {
+                methods.push((
+                    "missing_fields".to_owned(),
+                    "a method generated by `builder(getters)`",
+                ));
+            }
+        }
+        // Through which an enclosing builder uses this one as a sub-builder.
+        for hidden in ["__build_owned", "__build_ref", "__merge", "__sub_builder"] {
+            methods.push((hidden.to_owned(), "a hidden method of the builder"));
+        }
+
+        for (i, (name, setter, _)) in setters.iter().enumerate() {
+            let method = methods
+                .iter()
+                .find(|(method, _)| method == name)
+                .map(|(_, what)| what.to_string());
+            let other = method.or_else(|| {
+                setters[..i]
+                    .iter()
+                    .find(|(other, _, _)| other == name)
+                    .map(|(_, _, field)| format!("a setter of `{}`", field.unraw()))
+            });
+            if let Some(other) = other {
+                errors.push(Error::new_spanned(
+                    setter,
+                    format!("setter `{}` collides with {}", name, other),
+                ));
+            }
+        }
+    }
+
+    pub fn pattern(&self) -> Pattern {
+        if self.attrs.typestate {
+            Pattern::Owned
+        } else {
+            self.attrs.pattern.unwrap_or(Pattern::Mutable)
+        }
+    }
+
+    /// Root of the paths to `core` items in the generated code.
+    pub fn core(&self) -> TokenStream {
+        self.extern_crate("core")
+    }
+
+    /// Root of the paths to `serde` items in the generated code.
+    pub fn serde(&self) -> TokenStream {
+        self.extern_crate("serde")
+    }
+
+    /// Path to the crate `name`, either in the extern prelude or re-exported
+    /// under `#[builder(crate = "...")]`.
+    fn extern_crate(&self, name: &str) -> TokenStream {
+        let name = Ident::new(name, Span::call_site());
+        match &self.attrs.krate {
+            Some(krate) => quote!(#krate::#name),
+            None => quote!(::#name),
+        }
+    }
+
+    /// Whether the generated code may allocate: always, unless the struct
+    /// asks for `no_std` without `alloc`.
+    pub fn has_alloc(&self) -> bool {
+        !self.attrs.no_std || self.attrs.alloc
+    }
+
+    /// Root of the paths to `alloc` items in the generated code: `alloc`
+    /// itself in a `no_std` builder, `std` otherwise.
+    pub fn alloc(&self) -> TokenStream {
+        if self.attrs.no_std {
+            self.extern_crate("alloc")
+        } else {
+            self.extern_crate("std")
+        }
+    }
+
+    /// The type the error type uses to carry the error of a validation
+    /// function or sub-builder: a boxed error, or just a message when there is
+    /// no allocator.
+    pub fn error_payload(&self) -> TokenStream {
+        let core = self.core();
+        if !self.has_alloc() {
+            return quote!(&'static str);
+        }
+        let alloc = self.alloc();
+        quote! {
+            #alloc::boxed::Box<dyn #core::error::Error + #core::marker::Send + #core::marker::Sync>
+        }
+    }
+
+    /// The struct's own type, with its generic parameters.
+    pub fn target_ty(&self) -> TokenStream {
+        let target = self.target;
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        quote!(#target #ty_generics)
+    }
+
+    /// The struct's generic parameters as arguments, for spelling out the
+    /// type of a builder with more parameters of its own.
+    pub fn target_args(&self) -> Vec<TokenStream> {
+        self.generics
+            .params
+            .iter()
+            .map(|param| match param {
+                GenericParam::Lifetime(param) => param.lifetime.to_token_stream(),
+                GenericParam::Type(param) => param.ident.to_token_stream(),
+                GenericParam::Const(param) => param.ident.to_token_stream(),
+            })
+            .collect()
+    }
+
+    /// Whether `ty` mentions any of the struct's type parameters.
+    pub fn is_generic(&self, ty: &Type) -> bool {
+        ty::mentions_any(ty, self.generics.type_params().map(|param| &param.ident))
+    }
+
+    /// Whether a field has a default which `build_async()` awaits.
+    pub fn is_async(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|field| matches!(field.fallback, Fallback::Async(_)))
+    }
+
+    /// Whether `build()` can fail once every required field is set, because
+    /// of validation functions, sub-builders or fallible defaults. The
+    /// `build()` of a typestate builder which cannot returns the struct
+    /// itself.
+    pub fn is_fallible(&self) -> bool {
+        self.attrs.validate.is_some()
+            || self.fields.iter().any(|field| {
+                field.validate.is_some()
+                    || matches!(field.kind, Kind::SubBuilder(_))
+                    || matches!(field.fallback, Fallback::Try(_))
+            })
+    }
+
+    /// Whether `build()` needs the struct's own `Default` value.
+    pub fn uses_container_default(&self) -> bool {
+        self.fields
+            .iter()
+            .any(|field| matches!(field.fallback, Fallback::Container))
+    }
+}
+
+impl<'a> Field<'a> {
+    /// The field's name in CamelCase, for naming generated types and
+    /// variants after it.
+    pub fn camel_name(&self) -> String {
+        let mut camel = String::new();
+        for word in self.ident.unraw().to_string().split('_') {
+            let mut chars = word.chars();
+            if let Some(first) = chars.next() {
+                camel.extend(first.to_uppercase());
+                camel.push_str(chars.as_str());
+            }
+        }
+        camel
+    }
+
+    /// Whether the builder has storage for this field.
+    pub fn is_stored(&self) -> bool {
+        !matches!(self.kind, Kind::Skip)
+    }
+
+    pub fn is_required(&self) -> bool {
+        matches!(self.fallback, Fallback::Missing)
+    }
+
+    /// Whether `build()` can fail because the field was never set: a
+    /// required field, or one whose default only `build_async()` awaits.
+    pub fn can_be_missing(&self) -> bool {
+        matches!(self.fallback, Fallback::Missing | Fallback::Async(_))
+    }
+
+    /// Whether a setter taking the whole field value should be generated. An
+    /// `each` setter with the same name as the field replaces it.
+    pub fn has_all_at_once_setter(&self) -> bool {
+        match &self.kind {
+            Kind::Each { setter, .. } => *setter != self.ident,
+            Kind::Skip | Kind::SubBuilder(_) => false,
+            _ => true,
+        }
+    }
+
+    /// The type the builder stores, wrapped in an `Option`, for the field.
+    pub fn storage_ty(&self) -> &Type {
+        match &self.kind {
+            Kind::SubBuilder(builder_ty) => builder_ty,
+            _ => self.ty,
+        }
+    }
+
+    /// The type of value given to the field's setter, which its validation
+    /// function checks.
+    pub fn setter_ty(&self) -> &'a Type {
+        match self.kind {
+            Kind::Optional(inner) => inner,
+            _ => self.ty,
+        }
+    }
+
+    /// Parameter type and converted value for a setter argument `arg` of type
+    /// `ty`.
+    pub fn setter_input(
+        &self,
+        core: &TokenStream,
+        arg: &Ident,
+        ty: &Type,
+    ) -> (TokenStream, TokenStream) {
+        if self.into {
+            (
+                quote!(impl #core::convert::Into<#ty>),
+                quote!(#core::convert::Into::into(#arg)),
+            )
+        } else {
+            (quote!(#ty), quote!(#arg))
+        }
+    }
+}
+
+/// `camel` in snake case, a run of capitals such as `HTTP` in `HTTPRequest`
+/// making up a single word.
+fn snake_case(camel: &str) -> String {
+    let chars: Vec<char> = camel.chars().collect();
+    let mut snake = String::new();
+    for (i, &ch) in chars.iter().enumerate() {
+        if ch.is_uppercase() {
+            let after_word = i > 0 && !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            let starts_word = i > 0
+                && chars[i - 1].is_uppercase()
+                && chars.get(i + 1).is_some_and(|next| next.is_lowercase());
+            if after_word || starts_word {
+                snake.push('_');
+            }
+            snake.extend(ch.to_lowercase());
+        } else {
+            snake.push(ch);
+        }
+    }
+    snake
+}
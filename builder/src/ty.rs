@@ -0,0 +1,146 @@
+use quote::format_ident;
+use syn::visit::{self, Visit};
+use syn::{Error, GenericArgument, Ident, PathArguments, Result, Type, TypePath};
+
+/// Returns `T` if `ty` is spelled `Wrapper<T>` (or `path::to::Wrapper<T>`).
+pub fn unwrap<'a>(ty: &'a Type, wrapper: &str) -> Option<&'a Type> {
+    let path = match ty {
+        Type::Path(ty) if ty.qself.is_none() => &ty.path,
+        _ => return None,
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let args = match &segment.arguments {
+        PathArguments::AngleBracketed(args) if args.args.len() == 1 => &args.args,
+        _ => return None,
+    };
+    match &args[0] {
+        GenericArgument::Type(inner) => Some(inner),
+        _ => None,
+    }
+}
+
+/// The builder type derived for `ty`: `Name<Args>` becomes
+/// `NameBuilder<Args>`.
+pub fn builder_of(ty: &Type) -> Result<Type> {
+    if let Type::Path(path) = ty {
+        if path.qself.is_none() {
+            let mut path = path.clone();
+            if let Some(last) = path.path.segments.last_mut() {
+                last.ident = format_ident!("{}Builder", last.ident);
+                return Ok(Type::Path(path));
+            }
+        }
+    }
+    Err(Error::new_spanned(
+        ty,
+        "cannot name the builder of this type, give it with `sub_builder = \"...\"`",
+    ))
+}
+
+/// The kind of element accepted by `Extend` for a collection type.
+pub enum Item<'a> {
+    /// A sequence or set of `T`.
+    Single(&'a Type),
+    /// A map from `K` to `V`, extended with `(K, V)` pairs.
+    Pair(&'a Type, &'a Type),
+    /// Any other type; the element type is left to inference.
+    Unknown,
+}
+
+/// Works out the element type of a collection from its name.
+pub fn collection_item(ty: &Type) -> Item<'_> {
+    let segment = match ty {
+        Type::Path(ty) if ty.qself.is_none() => ty.path.segments.last(),
+        _ => None,
+    };
+    let (name, args) = match segment {
+        Some(segment) => match &segment.arguments {
+            PathArguments::AngleBracketed(args) => (&segment.ident, &args.args),
+            _ => return Item::Unknown,
+        },
+        None => return Item::Unknown,
+    };
+    let mut types = args.iter().filter_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    });
+
+    let sequences = [
+        "Vec",
+        "VecDeque",
+        "LinkedList",
+        "HashSet",
+        "BTreeSet",
+        "BinaryHeap",
+    ];
+    if sequences.iter().any(|sequence| name == sequence) {
+        if let Some(item) = types.next() {
+            return Item::Single(item);
+        }
+    } else if name == "HashMap" || name == "BTreeMap" {
+        if let (Some(key), Some(value)) = (types.next(), types.next()) {
+            return Item::Pair(key, value);
+        }
+    }
+    Item::Unknown
+}
+
+/// Whether `ty` is made up of primitives, shared references and `Option`s of
+/// them only, so that values of it can be copied and dropped in a `const fn`.
+pub fn is_trivial(ty: &Type) -> bool {
+    match ty {
+        Type::Reference(ty) => ty.mutability.is_none(),
+        Type::Paren(ty) => is_trivial(&ty.elem),
+        Type::Array(ty) => is_trivial(&ty.elem),
+        Type::Tuple(ty) => ty.elems.iter().all(is_trivial),
+        Type::Path(path) if path.qself.is_none() => {
+            if let Some(inner) = unwrap(ty, "Option") {
+                return is_trivial(inner);
+            }
+            let primitives = [
+                "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "u8",
+                "u16", "u32", "u64", "u128", "usize",
+            ];
+            match path.path.get_ident() {
+                Some(ident) => primitives.iter().any(|primitive| ident == primitive),
+                None => false,
+            }
+        }
+        _ => false,
+    }
+}
+
+/// Whether any path in `ty` starts with one of `idents`, such as `T` or
+/// `T::Assoc` for a type parameter `T`.
+pub fn mentions_any<'a, I>(ty: &Type, idents: I) -> bool
+where
+    I: IntoIterator<Item = &'a Ident>,
+{
+    struct Mentions<'a> {
+        idents: Vec<&'a Ident>,
+        found: bool,
+    }
+
+    impl<'ast, 'a> Visit<'ast> for Mentions<'a> {
+        fn visit_type_path(&mut self, ty: &'ast TypePath) {
+            if ty.qself.is_none() {
+                if let Some(first) = ty.path.segments.first() {
+                    if self.idents.contains(&&first.ident) {
+                        self.found = true;
+                    }
+                }
+            }
+            visit::visit_type_path(self, ty);
+        }
+    }
+
+    let mut mentions = Mentions {
+        idents: idents.into_iter().collect(),
+        found: false,
+    };
+    mentions.visit_type(ty);
+    mentions.found
+}
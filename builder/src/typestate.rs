@@ -0,0 +1,333 @@
+//! Expansion of `#[builder(typestate)]`.
+//!
+//! Every required field gets a type parameter on the builder which is `()`
+//! until the field's setter has been called and `(T,)` afterwards. `build()`
+//! is bounded on one marker trait per required field, implemented only for
+//! `(T,)`, whose `on_unimplemented` message names the missing field.
+//!
+//! The builder holds a `PhantomData` of the struct so that generic parameters
+//! used only by required fields still count as used.
+
+use crate::doc;
+use crate::error;
+use crate::expand;
+use crate::model::{Builder, Field};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{parse_quote, Ident, LitStr};
+
+struct State<'a> {
+    field: &'a Field<'a>,
+    param: Ident,
+    marker: Ident,
+}
+
+pub fn expand(builder: &Builder) -> TokenStream {
+    let core = builder.core();
+    let Builder {
+        vis,
+        target,
+        ctor,
+        constructor,
+        ident: builder_ident,
+        build_fn,
+        generics: target_generics,
+        fields,
+        ..
+    } = builder;
+
+    let states: Vec<State> = fields
+        .iter()
+        .filter(|field| field.is_required())
+        .map(|field| {
+            let camel = field.camel_name();
+            State {
+                field,
+                param: format_ident!("__{}", camel),
+                marker: format_ident!("__{}{}", builder_ident, camel),
+            }
+        })
+        .collect();
+    let state_of = |field: &Field| states.iter().find(|state| state.field.ident == field.ident);
+
+    // The struct's own generic parameters come first, followed by one
+    // parameter per required field defaulting to the unset state.
+    let mut generics = (*target_generics).clone();
+    for State { param, .. } in &states {
+        generics.params.push(parse_quote!(#param = ()));
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let (target_impl_generics, target_ty_generics, target_where_clause) =
+        target_generics.split_for_impl();
+    let target_ty = builder.target_ty();
+    let target_args = builder.target_args();
+
+    let stored = || fields.iter().filter(|field| field.is_stored());
+    let storage = stored().map(|field| {
+        let ident = &field.ident;
+        let ty = field.storage_ty();
+        match state_of(field) {
+            Some(State { param, .. }) => quote!(#ident: #param),
+            None => quote!(#ident: #core::option::Option<#ty>),
+        }
+    });
+    let init = stored().map(|field| {
+        let ident = &field.ident;
+        match state_of(field) {
+            Some(_) => quote!(#ident: ()),
+            None => quote!(#ident: #core::option::Option::None),
+        }
+    });
+
+    let markers = states.iter().map(|state| {
+        let State { field, marker, .. } = state;
+        let name = field.ident.unraw();
+        let message = LitStr::new(
+            &format!("missing required field `{}` in `{}`", name, builder_ident),
+            name.span(),
+        );
+        let label = LitStr::new(
+            &format!("call `.{}(...)` before `.{}()`", name, build_fn),
+            name.span(),
+        );
+        quote! {
+            #[doc(hidden)]
+            #[diagnostic::on_unimplemented(message = #message, label = #label)]
+            #vis trait #marker<T> {
+                fn value(self) -> T;
+                fn get(&self) -> &T;
+            }
+
+            impl<T> #marker<T> for (T,) {
+                fn value(self) -> T {
+                    self.0
+                }
+
+                fn get(&self) -> &T {
+                    &self.0
+                }
+            }
+        }
+    });
+
+    let setters = fields.iter().map(|field| {
+        let Field { ident, ty, .. } = field;
+
+        if state_of(field).is_some() {
+            let output = states.iter().map(|other| {
+                if other.field.ident == *ident {
+                    quote!((#ty,))
+                } else {
+                    let param = &other.param;
+                    quote!(#param)
+                }
+            });
+            let (arg, value) = field.setter_input(&core, ident, ty);
+            let moved = stored().map(|other| {
+                let other = &other.ident;
+                if other == ident {
+                    quote!(#other: (#value,))
+                } else {
+                    quote!(#other: self.#other)
+                }
+            });
+            let output = quote!(#builder_ident<#(#target_args,)* #(#output),*>);
+            let try_setter = expand::try_setter(builder, field, quote!(self), output.clone());
+            let doc = doc::setter(field);
+            return quote! {
+                #doc
+                pub fn #ident(self, #ident: #arg) -> #output {
+                    #builder_ident {
+                        #(#moved,)*
+                        __marker: self.__marker,
+                    }
+                }
+
+                #try_setter
+            };
+        }
+
+        expand::setters(builder, field)
+    });
+
+    let bounds = states.iter().map(|state| {
+        let State {
+            field,
+            param,
+            marker,
+        } = state;
+        let ty = field.ty;
+        quote!(#param: #marker<#ty>)
+    });
+    let container_default = expand::container_default(builder);
+    let build_fields = fields.iter().map(|field| {
+        let Field { ident, member, .. } = field;
+        match state_of(field) {
+            Some(State { param, marker, .. }) => {
+                let ty = field.ty;
+                quote!(#member: <#param as #marker<#ty>>::value(self.#ident))
+            }
+            None => expand::build_field(builder, field, false),
+        }
+    });
+
+    // The values of required fields are behind their marker trait, the rest
+    // are checked the same way as in a runtime builder.
+    let validate_required = states.iter().filter_map(|state| {
+        let State {
+            field,
+            param,
+            marker,
+        } = state;
+        let path = field.validate.as_ref()?;
+        let Field { ident, ty, .. } = field;
+        let invalid = error::invalid(builder, field);
+        Some(quote! {
+            if let #core::result::Result::Err(__err) = #path(<#param as #marker<#ty>>::get(&self.#ident)) {
+                #invalid;
+            }
+        })
+    });
+    let validate_others = expand::validate_fields(
+        builder,
+        fields.iter().filter(|field| state_of(field).is_none()),
+    );
+
+    // Without validation functions, sub-builders or fallible defaults nothing
+    // can go wrong once every required field is set, so there is no need for
+    // a Result.
+    let validate = expand::validate(builder);
+    let (output, ret, error) = if builder.is_fallible() {
+        let error_ty = error::output(builder);
+        (
+            quote!(#core::result::Result<#target_ty, #error_ty>),
+            quote! {
+                #validate
+                #core::result::Result::Ok(__value)
+            },
+            Some(error::expand(builder, &[])),
+        )
+    } else {
+        (target_ty.clone(), quote!(__value), None)
+    };
+
+    let builder_doc = doc::builder(builder);
+    let constructor_doc = doc::constructor(builder);
+    let build_doc = doc::build_fn(builder);
+    let sub_builder = sub_builder(builder, states.len());
+
+    quote! {
+        #(#markers)*
+
+        #builder_doc
+        #vis struct #builder_ident #generics #where_clause {
+            #(#storage,)*
+            __marker: #core::marker::PhantomData<fn() -> #target_ty>,
+        }
+
+        impl #target_impl_generics #target #target_ty_generics #target_where_clause {
+            #constructor_doc
+            #vis fn #constructor() -> #builder_ident #target_ty_generics {
+                #builder_ident {
+                    #(#init,)*
+                    __marker: #core::marker::PhantomData,
+                }
+            }
+        }
+
+        impl #impl_generics #builder_ident #ty_generics #where_clause {
+            #(#setters)*
+
+            #build_doc
+            pub fn #build_fn(self) -> #output
+            where
+                #(#bounds,)*
+            {
+                #(#validate_required)*
+                #validate_others
+                #container_default
+                let __value = #ctor {
+                    #(#build_fields,)*
+                };
+                #ret
+            }
+        }
+
+        #sub_builder
+
+        #error
+    }
+}
+
+/// The methods an enclosing builder calls on a sub-builder, for the builder
+/// in its initial state. A typestate builder cannot be nested, as its setters
+/// change its type: `__sub_builder()` is bounded on a trait which is never
+/// implemented, whose message names the builder, and the others are only
+/// there so that this is the one error an enclosing builder gets.
+fn sub_builder(builder: &Builder, states: usize) -> TokenStream {
+    let core = builder.core();
+    let Builder {
+        vis,
+        target,
+        constructor,
+        ident: builder_ident,
+        generics,
+        ..
+    } = builder;
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let target_ty = builder.target_ty();
+    let target_args = builder.target_args();
+    let unset = (0..states).map(|_| quote!(()));
+    let builder_ty = quote!(#builder_ident<#(#target_args,)* #(#unset),*>);
+    let output = quote!(#core::result::Result<#target_ty, #core::convert::Infallible>);
+
+    let nested = format_ident!("__{}SubBuilder", builder_ident);
+    let message = LitStr::new(
+        &format!(
+            "`{}` is a typestate builder, which cannot be nested with `sub_builder`",
+            builder_ident,
+        ),
+        builder_ident.span(),
+    );
+    let label = LitStr::new(
+        "set this field to a built value rather than through `sub_builder`",
+        builder_ident.span(),
+    );
+
+    quote! {
+        #[doc(hidden)]
+        #[diagnostic::on_unimplemented(message = #message, label = #label)]
+        #vis trait #nested {}
+
+        impl #impl_generics #core::default::Default for #builder_ty #where_clause {
+            fn default() -> Self {
+                #target::#constructor()
+            }
+        }
+
+        impl #impl_generics #builder_ty #where_clause {
+            #[doc(hidden)]
+            pub fn __sub_builder()
+            where
+                for<'__a> Self: #nested,
+            {
+            }
+
+            #[doc(hidden)]
+            pub fn __build_owned(self) -> #output {
+                #core::unreachable!()
+            }
+
+            #[doc(hidden)]
+            pub fn __build_ref(&self) -> #output {
+                #core::unreachable!()
+            }
+
+            #[doc(hidden)]
+            pub fn __merge(&mut self, __other: Self) {
+                #core::unreachable!()
+            }
+        }
+    }
+}
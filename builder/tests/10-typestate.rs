@@ -0,0 +1,43 @@
+// With #[builder(typestate)] the builder tracks which required fields have
+// been set in its own type, so a call to build() only compiles once every
+// field that is neither an Option nor an `each` field has been given a value.
+// In exchange build() no longer needs to return a Result.
+//
+// Setters take and return the builder by value because each call to the
+// setter of a required field changes the builder's type.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let command = Command::builder()
+        .arg("build".to_owned())
+        .executable("cargo".to_owned())
+        .arg("--release".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert!(command.env.is_empty());
+    assert!(command.current_dir.is_none());
+
+    // Setting a required field a second time replaces its value.
+    let command = Command::builder()
+        .executable("rustc".to_owned())
+        .current_dir("..".to_owned())
+        .executable("cargo".to_owned())
+        .build();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+}
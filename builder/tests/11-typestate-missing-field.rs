@@ -0,0 +1,21 @@
+// A typestate builder whose required fields have not all been set has no
+// usable build() method. The error should name the field that is missing
+// rather than only saying that a trait bound is unsatisfied.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Command {
+    executable: String,
+    current_dir: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+}
+
+fn main() {
+    let _ = Command::builder()
+        .arg("build".to_owned())
+        .executable("cargo".to_owned())
+        .build();
+}
@@ -0,0 +1,80 @@
+// Fields other than Option and `each` fields can be made optional by giving
+// them a default value.
+//
+//   - #[builder(default)] on a field uses the field type's Default impl.
+//
+//   - #[builder(default = "expr")] on a field evaluates the expression inside
+//     of build() whenever the field was never set.
+//
+//   - #[builder(default)] on the struct falls back to the struct's own Default
+//     impl for every field which was not set and has no default of its own.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(default)]
+    args: Vec<String>,
+    #[builder(default = "vec![\"PATH=/bin\".to_owned()]")]
+    env: Vec<String>,
+    #[builder(default = "Some(\"..\".to_owned())")]
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(default)]
+pub struct Limits {
+    open_files: u64,
+    #[builder(default = "1")]
+    threads: u32,
+    stack_size: usize,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Limits {
+            open_files: 1024,
+            threads: 8,
+            stack_size: 2 << 20,
+        }
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Job {
+    name: String,
+    #[builder(default = "3")]
+    retries: u32,
+}
+
+fn main() {
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .build()
+        .unwrap();
+
+    assert!(command.args.is_empty());
+    assert_eq!(command.env, vec!["PATH=/bin"]);
+    assert_eq!(command.current_dir.as_deref(), Some(".."));
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .env(vec![])
+        .build()
+        .unwrap();
+
+    assert!(command.env.is_empty());
+
+    let limits = Limits::builder().stack_size(1 << 20).build().unwrap();
+
+    assert_eq!(limits.open_files, 1024);
+    assert_eq!(limits.threads, 1);
+    assert_eq!(limits.stack_size, 1 << 20);
+
+    let job = Job::builder().name("fetch".to_owned()).build();
+
+    assert_eq!(job.name, "fetch");
+    assert_eq!(job.retries, 3);
+}
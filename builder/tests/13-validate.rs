@@ -0,0 +1,68 @@
+// A struct level #[builder(validate = "path::to::fn")] names a function which
+// build() calls on the fully assembled struct before handing it out. This is
+// the place to check invariants that span several fields. The function returns
+// Result<(), E> for any error type E which converts into the error returned by
+// build().
+//
+// A typestate builder has nothing that can fail at runtime on its own, but once
+// it has a validation function its build() returns a Result too.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(validate = "checks::command")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "env")]
+    env: Vec<String>,
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate, validate = "checks::job")]
+pub struct Job {
+    name: String,
+    retries: u32,
+}
+
+mod checks {
+    pub fn command(command: &super::Command) -> Result<(), String> {
+        if !command.env.is_empty() && command.current_dir.is_none() {
+            return Err("current_dir must be set when env is non-empty".to_owned());
+        }
+        Ok(())
+    }
+
+    pub fn job(job: &super::Job) -> Result<(), std::fmt::Error> {
+        if job.retries > 10 {
+            return Err(std::fmt::Error);
+        }
+        Ok(())
+    }
+}
+
+fn main() {
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .env("RUST_LOG=debug".to_owned())
+        .build()
+        .err()
+        .unwrap();
+
+    assert_eq!(err.to_string(), "current_dir must be set when env is non-empty");
+
+    let command = Command::builder()
+        .executable("cargo".to_owned())
+        .env("RUST_LOG=debug".to_owned())
+        .current_dir("..".to_owned())
+        .build()
+        .unwrap();
+
+    assert_eq!(command.env, vec!["RUST_LOG=debug"]);
+
+    let job = Job::builder().name("fetch".to_owned()).retries(3).build();
+    assert!(job.is_ok());
+
+    let job = Job::builder().name("fetch".to_owned()).retries(30).build();
+    assert!(job.is_err());
+}
@@ -0,0 +1,73 @@
+// build() returns a dedicated error enum generated alongside the builder,
+// named after it: CommandBuilderError. It has one variant per field which
+// can be missing when build() is called, so that callers can match on which
+// one it was, and a Validation variant holding whatever error the validation
+// function reported. The enum implements std::error::Error and Display.
+//
+// A struct level #[builder(error = "MyError")] makes build() return the
+// caller's own error type instead, which must implement
+// From<CommandBuilderError>.
+
+use derive_builder::Builder;
+use std::error::Error;
+
+#[derive(Builder)]
+#[builder(validate = "Command::check")]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+impl Command {
+    fn check(&self) -> Result<(), &'static str> {
+        if self.executable.is_empty() {
+            return Err("executable must not be empty");
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    Usage(String),
+}
+
+impl From<JobBuilderError> for CliError {
+    fn from(err: JobBuilderError) -> Self {
+        CliError::Usage(format!("invalid job: {}", err))
+    }
+}
+
+#[derive(Builder)]
+#[builder(error = "CliError")]
+pub struct Job {
+    name: String,
+}
+
+fn main() {
+    let err = Command::builder().build().err().unwrap();
+
+    match err {
+        CommandBuilderError::MissingExecutable => {}
+        CommandBuilderError::Validation(_) => unreachable!(),
+    }
+    assert_eq!(err.missing_field(), Some("executable"));
+    assert_eq!(err.to_string(), "missing required field `executable`");
+
+    let err = Command::builder()
+        .executable(String::new())
+        .build()
+        .err()
+        .unwrap();
+
+    assert!(matches!(err, CommandBuilderError::Validation(_)));
+    assert_eq!(err.missing_field(), None);
+    assert_eq!(err.to_string(), "executable must not be empty");
+    assert!(err.source().is_some());
+
+    let err: CliError = Job::builder().build().err().unwrap();
+    let CliError::Usage(msg) = err;
+    assert_eq!(msg, "invalid job: missing required field `name`");
+}
@@ -0,0 +1,63 @@
+// The builder carries the same generic parameters, lifetimes, bounds and where
+// clause as the struct it builds. Fields whose type mentions a lifetime or a
+// type parameter are stored in the builder as they are, and an Option around a
+// borrowed type is still recognized as optional.
+//
+// Since build() clones values out of the builder, it is only available when the
+// fields whose type depends on a type parameter are Clone.
+
+use derive_builder::Builder;
+
+pub trait Body {
+    fn len(&self) -> usize;
+}
+
+impl Body for String {
+    fn len(&self) -> usize {
+        String::len(self)
+    }
+}
+
+#[derive(Builder)]
+pub struct Request<'a, B: Body>
+where
+    B: Send,
+{
+    url: &'a str,
+    body: B,
+    user_agent: Option<&'a str>,
+    #[builder(each = "header")]
+    headers: Vec<(&'a str, String)>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Matrix<T: Copy + Default, const N: usize> {
+    rows: [[T; N]; N],
+    #[builder(default)]
+    label: String,
+}
+
+fn main() {
+    let url = String::from("https://example.com");
+
+    let request = Request::builder()
+        .url(&url)
+        .body("hello".to_owned())
+        .user_agent("curl")
+        .header(("Accept", "*/*".to_owned()))
+        .build()
+        .unwrap();
+
+    assert_eq!(request.url, "https://example.com");
+    assert_eq!(request.body.len(), 5);
+    assert_eq!(request.user_agent, Some("curl"));
+    assert_eq!(request.headers.len(), 1);
+
+    let err = Request::<String>::builder().url(&url).build().err().unwrap();
+    assert_eq!(err.missing_field(), Some("body"));
+
+    let identity = Matrix::builder().rows([[1, 0], [0, 1]]).build();
+    assert_eq!(identity.rows[1][1], 1);
+    assert!(identity.label.is_empty());
+}
@@ -0,0 +1,16 @@
+// The bounds of the struct apply to its builder too, so a builder for a type
+// argument that does not satisfy them cannot be created.
+
+use derive_builder::Builder;
+
+pub trait Body {}
+
+#[derive(Builder)]
+pub struct Request<'a, B: Body> {
+    url: &'a str,
+    body: B,
+}
+
+fn main() {
+    let _ = Request::builder().url("/").body(Vec::<u8>::new());
+}
@@ -0,0 +1,59 @@
+// The signature of the generated setters can be adjusted with
+// #[builder(setter(...))].
+//
+//   - setter(into) makes the setter accept any `impl Into<T>` for a field of
+//     type T. It is accepted on a single field or on the struct, where it
+//     applies to every setter including `each` setters.
+//
+//   - setter(strip_option = false) opts an Option<T> field out of having a
+//     setter that takes T. The setter takes the Option<T> itself, which lets
+//     callers pass through a value they already hold as an Option.
+//
+//   - setter(skip) generates no setter at all. The field is always built from
+//     its default.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(setter(into))]
+    executable: String,
+    #[builder(each = "arg", setter(into))]
+    args: Vec<String>,
+    #[builder(setter(strip_option = false))]
+    current_dir: Option<String>,
+    #[builder(setter(skip))]
+    pid: Option<u32>,
+    #[builder(setter(skip), default = "42")]
+    exit_code: i32,
+}
+
+#[derive(Builder)]
+#[builder(typestate, setter(into))]
+pub struct Job {
+    name: String,
+    queue: Option<String>,
+}
+
+fn main() {
+    let current_dir: Option<String> = None;
+
+    let command = Command::builder()
+        .executable("cargo")
+        .arg("build")
+        .arg(String::from("--release"))
+        .current_dir(current_dir)
+        .build()
+        .unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build", "--release"]);
+    assert_eq!(command.current_dir, None);
+    assert_eq!(command.pid, None);
+    assert_eq!(command.exit_code, 42);
+
+    let job = Job::builder().queue("default").name("fetch").build();
+
+    assert_eq!(job.name, "fetch");
+    assert_eq!(job.queue.as_deref(), Some("default"));
+}
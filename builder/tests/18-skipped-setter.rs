@@ -0,0 +1,17 @@
+// A field with setter(skip) has no setter on the builder.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Command {
+    executable: String,
+    #[builder(setter(skip))]
+    pid: Option<u32>,
+}
+
+fn main() {
+    let _ = Command::builder()
+        .executable("cargo".to_owned())
+        .pid(1)
+        .build();
+}
@@ -0,0 +1,59 @@
+// #[builder(pattern = "...")] chooses how setters and build() receive the
+// builder.
+//
+//   - "mutable", the default, is what the earlier tests use: setters take and
+//     return &mut self, and build(&self) clones every field out of the builder.
+//
+//   - "owned" setters take self by value and return it, and build(self)
+//     consumes the builder. Fields never get cloned, so they do not need to be
+//     Clone.
+//
+//   - "immutable" setters take &self and return a modified copy of the builder,
+//     leaving the original untouched. This makes it easy to derive several
+//     values from a common starting point.
+
+use derive_builder::Builder;
+use std::fs::File;
+
+#[derive(Builder)]
+#[builder(pattern = "owned")]
+pub struct Log {
+    file: File,
+    #[builder(each = "tag")]
+    tags: Vec<String>,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "mutable")]
+pub struct Command {
+    executable: String,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable")]
+pub struct Request {
+    method: String,
+    path: Option<String>,
+}
+
+fn main() {
+    let log = Log::builder()
+        .file(File::open("/dev/null").unwrap())
+        .tag("audit".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(log.tags, vec!["audit"]);
+    let _: File = log.file;
+
+    let mut builder = Command::builder();
+    builder.executable("cargo".to_owned());
+    let command = builder.build().unwrap();
+    assert_eq!(command.executable, "cargo");
+
+    let get = Request::builder().method("GET".to_owned());
+    let index = get.path("/index.html".to_owned()).build().unwrap();
+    let root = get.build().unwrap();
+    assert_eq!(index.method, "GET");
+    assert_eq!(index.path.as_deref(), Some("/index.html"));
+    assert_eq!(root.path, None);
+}
@@ -0,0 +1,12 @@
+// A typestate builder changes type with every required field that is set, so
+// it can only be used by value.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate, pattern = "mutable")]
+pub struct Command {
+    executable: String,
+}
+
+fn main() {}
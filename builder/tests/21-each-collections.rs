@@ -0,0 +1,59 @@
+// The `each` attribute works on any collection which implements Default and
+// Extend, not only Vec. For the sets, queues and maps of the standard library
+// the single item setter takes the element type. For HashMap and BTreeMap it
+// takes the key and the value as two arguments.
+//
+// For other collections the setter is generic over anything the collection can
+// be extended with.
+//
+// As with Vec, an `each` setter with the same name as the field replaces the
+// setter which takes the whole collection at once.
+
+use derive_builder::Builder;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+
+#[derive(Clone, Default)]
+pub struct Flags(Vec<&'static str>);
+
+impl Extend<&'static str> for Flags {
+    fn extend<I: IntoIterator<Item = &'static str>>(&mut self, iter: I) {
+        self.0.extend(iter);
+    }
+}
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(each = "env", setter(into))]
+    env: HashMap<String, String>,
+    #[builder(each = "feature")]
+    features: BTreeSet<String>,
+    #[builder(each = "stage")]
+    stages: VecDeque<u8>,
+    #[builder(each = "flag")]
+    flags: Flags,
+}
+
+fn main() {
+    let command = Command::builder()
+        .env("RUST_LOG", "debug")
+        .env("RUST_BACKTRACE".to_owned(), "1")
+        .feature("serde".to_owned())
+        .feature("derive".to_owned())
+        .stages(VecDeque::from(vec![1, 2]))
+        .stage(3)
+        .flag("--locked")
+        .build()
+        .unwrap();
+
+    assert_eq!(command.env["RUST_LOG"], "debug");
+    assert_eq!(command.env["RUST_BACKTRACE"], "1");
+    assert_eq!(
+        command.features.into_iter().collect::<Vec<_>>(),
+        vec!["derive", "serde"],
+    );
+    assert_eq!(command.stages, vec![1, 2, 3]);
+    assert_eq!(command.flags.0, vec!["--locked"]);
+
+    let command = Command::builder().build().unwrap();
+    assert!(command.env.is_empty());
+}
@@ -0,0 +1,43 @@
+// Tuple structs get a builder too. Their setters are named after the
+// position of the field, `_0`, `_1` and so on, unless a field gives its setter
+// a name of its own with #[builder(name = "...")].
+//
+// Every other field option works the same as on a struct with named fields.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+pub struct Rgb(u8, u8, #[builder(name = "blue")] u8);
+
+#[derive(Builder)]
+pub struct Tagged(
+    String,
+    #[builder(each = "tag")] Vec<String>,
+    Option<u32>,
+);
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Point(#[builder(name = "x")] i32, #[builder(name = "y")] i32);
+
+fn main() {
+    let color = Rgb::builder()._0(255)._1(128).blue(0).build().unwrap();
+    assert_eq!((color.0, color.1, color.2), (255, 128, 0));
+
+    let err = Rgb::builder()._0(255).blue(0).build().err().unwrap();
+    assert_eq!(err.to_string(), "missing required field `_1`");
+    assert!(matches!(err, RgbBuilderError::Missing1));
+
+    let tagged = Tagged::builder()
+        ._0("item".to_owned())
+        .tag("a".to_owned())
+        .tag("b".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(tagged.0, "item");
+    assert_eq!(tagged.1, vec!["a", "b"]);
+    assert_eq!(tagged.2, None);
+
+    let point = Point::builder().y(2).x(1).build();
+    assert_eq!((point.0, point.1), (1, 2));
+}
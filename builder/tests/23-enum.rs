@@ -0,0 +1,84 @@
+// On an enum the derive generates one builder per variant. The builder of
+// variant `Circle` of enum `Shape` is called `ShapeCircleBuilder` and is
+// created by `Shape::circle_builder()`; its build() returns a `Shape`. A run
+// of capitals counts as one word, so that variant `HTTPRequest` is built by
+// `Message::http_request_builder()`.
+//
+// Variants with named fields, tuple variants and unit variants are all
+// supported, and struct level options such as #[builder(typestate)] or
+// #[builder(pattern = "...")] apply to the builder of every variant.
+//
+// Each builder has its own error type, `ShapeCircleBuilderError` here, naming
+// the fields of that variant.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub enum Shape {
+    Circle {
+        radius: f64,
+        label: Option<String>,
+    },
+    Polygon(#[builder(each = "vertex")] Vec<(f64, f64)>),
+    Empty,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub enum Message<T> {
+    Request { id: u64, body: T },
+    Cancel(#[builder(name = "id")] u64),
+    HTTPRequest { url: String },
+}
+
+fn main() {
+    let circle = Shape::circle_builder().radius(1.5).build().unwrap();
+    assert_eq!(
+        circle,
+        Shape::Circle {
+            radius: 1.5,
+            label: None,
+        }
+    );
+
+    let err = Shape::circle_builder().build().unwrap_err();
+    assert!(matches!(err, ShapeCircleBuilderError::MissingRadius));
+
+    let polygon = Shape::polygon_builder()
+        .vertex((0.0, 0.0))
+        .vertex((1.0, 0.0))
+        .vertex((0.0, 1.0))
+        .build()
+        .unwrap();
+    assert_eq!(
+        polygon,
+        Shape::Polygon(vec![(0.0, 0.0), (1.0, 0.0), (0.0, 1.0)])
+    );
+
+    assert_eq!(Shape::empty_builder().build().unwrap(), Shape::Empty);
+
+    let request = Message::request_builder()
+        .body("ping".to_owned())
+        .id(7)
+        .build();
+    assert_eq!(
+        request,
+        Message::Request {
+            id: 7,
+            body: "ping".to_owned(),
+        }
+    );
+
+    let cancel: Message<String> = Message::cancel_builder().id(7).build();
+    assert_eq!(cancel, Message::Cancel(7));
+
+    let http: Message<String> = Message::http_request_builder()
+        .url("https://example.com".to_owned())
+        .build();
+    assert_eq!(
+        http,
+        Message::HTTPRequest {
+            url: "https://example.com".to_owned(),
+        }
+    );
+}
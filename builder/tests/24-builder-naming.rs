@@ -0,0 +1,55 @@
+// The names the earlier tests rely on can be changed with struct level
+// options, to fit the naming rules of the API the struct is part of:
+//
+//   - #[builder(name = "...")] names the builder type, in place of
+//     `{Struct}Builder`. The error type follows it as `{Name}Error`.
+//
+//   - #[builder(constructor = "...")] names the associated function on the
+//     struct which returns a new builder, in place of `builder`.
+//
+//   - #[builder(build_fn = "...")] names the method which builds the struct,
+//     in place of `build`.
+//
+//   - #[builder(vis = "...")] gives the builder, its constructor and its error
+//     type a visibility other than the struct's own, for example to keep the
+//     builder of a public struct out of the crate's public API.
+
+mod api {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(
+        name = "CmdSpec",
+        vis = "pub(crate)",
+        constructor = "new_builder",
+        build_fn = "finish"
+    )]
+    pub struct Command {
+        pub executable: String,
+        #[builder(each = "arg")]
+        pub args: Vec<String>,
+    }
+
+    #[derive(Builder)]
+    #[builder(typestate, build_fn = "finish")]
+    pub struct Point {
+        pub x: i32,
+        pub y: i32,
+    }
+}
+
+use api::{CmdSpec, CmdSpecError, Command, Point};
+
+fn main() {
+    let mut builder: CmdSpec = Command::new_builder();
+    builder.executable("cargo".to_owned()).arg("build".to_owned());
+    let command = builder.finish().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["build"]);
+
+    let err: CmdSpecError = Command::new_builder().finish().err().unwrap();
+    assert_eq!(err.missing_field(), Some("executable"));
+
+    let point = Point::builder().x(1).y(2).finish();
+    assert_eq!((point.x, point.y), (1, 2));
+}
@@ -0,0 +1,18 @@
+// A builder made private with #[builder(vis = "...")] cannot be named or
+// created outside the module of its struct, even though the struct itself is
+// public.
+
+mod api {
+    use derive_builder::Builder;
+
+    #[derive(Builder)]
+    #[builder(vis = "pub(self)")]
+    pub struct Command {
+        pub executable: String,
+    }
+}
+
+fn main() {
+    let _ = api::Command::builder();
+    let _: api::CommandBuilder;
+}
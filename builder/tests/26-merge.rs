@@ -0,0 +1,73 @@
+// With #[builder(merge)], builders have a merge() method which copies every
+// field set on another builder onto this one. Fields the other builder leaves
+// unset keep whatever value this builder already has. #[builder(serde)]
+// implies it, deserialized builders being meant for this. A sub-builder set
+// on both builders is merged the same way, field by field, rather than
+// replaced as a whole.
+//
+// Builders without the option have no merge() method, so a struct may keep a
+// field called `merge` with its setter.
+//
+// This makes a builder a convenient way of layering partial configuration:
+// start from the defaults, then merge in what came from a config file, then
+// what came from the environment, and build once at the end.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Retry {
+    #[builder(default = "1")]
+    attempts: u32,
+    #[builder(default = "10")]
+    backoff: u64,
+}
+
+#[derive(Builder)]
+#[builder(merge)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(sub_builder)]
+    retry: Retry,
+}
+
+#[derive(Builder)]
+pub struct Strategy {
+    merge: bool,
+}
+
+fn main() {
+    let mut defaults = Command::builder();
+    defaults
+        .executable("cargo".to_owned())
+        .arg("build".to_owned())
+        .current_dir("/".to_owned());
+    defaults.retry().attempts(5);
+
+    let mut file = Command::builder();
+    file.args(vec!["test".to_owned(), "--release".to_owned()]);
+
+    let mut env = Command::builder();
+    env.current_dir("..".to_owned());
+    env.retry().backoff(99);
+
+    defaults.merge(file);
+    defaults.merge(env);
+    let command = defaults.build().unwrap();
+
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["test", "--release"]);
+    assert_eq!(command.current_dir.unwrap(), "..");
+    assert_eq!(
+        command.retry,
+        Retry {
+            attempts: 5,
+            backoff: 99,
+        }
+    );
+
+    let strategy = Strategy::builder().merge(true).build().unwrap();
+    assert!(strategy.merge);
+}
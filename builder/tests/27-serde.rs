@@ -0,0 +1,40 @@
+// With the `serde` feature of this crate enabled, #[builder(serde)] makes the
+// builder implement serde::Deserialize. Every field of the builder is optional
+// in the input, so a builder can be loaded from a partial config and merged
+// with others before building.
+//
+// Builders are deserialized field by field under the names of their setters.
+// A typestate builder, whose fields are tracked in its type, cannot be
+// deserialized.
+//
+// The crate using this needs a dependency on serde with the `derive` feature.
+// Builders without #[builder(serde)] are unaffected by the feature, so their
+// fields need not be deserializable.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(serde)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+}
+
+fn main() {
+    let mut base: CommandBuilder = serde_json::from_str(
+        r#"{ "executable": "cargo", "args": ["build"], "current_dir": "/" }"#,
+    )
+    .unwrap();
+    let overrides: CommandBuilder = serde_json::from_str(r#"{ "args": ["test"] }"#).unwrap();
+    base.merge(overrides);
+
+    let command = base.build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.args, vec!["test"]);
+    assert_eq!(command.current_dir.unwrap(), "/");
+
+    let empty: CommandBuilder = serde_json::from_str("{}").unwrap();
+    assert_eq!(empty.build().err().unwrap().missing_field(), Some("executable"));
+}
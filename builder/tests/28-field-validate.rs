@@ -0,0 +1,96 @@
+// A field can name a validation function with #[builder(validate = "...")].
+// It takes a reference to the value given to the field's setter and returns a
+// Result whose error converts into Box<dyn Error + Send + Sync>, the same as a
+// struct level validation function.
+//
+// Adding #[builder(try_setter)] generates a second setter, prefixed with
+// `try_`, which runs the validation function straight away and hands back
+// its error, so the error is reported where the bad value was supplied:
+//
+//     impl CommandBuilder {
+//         fn executable(&mut self, executable: String) -> &mut Self {...}
+//
+//         fn try_executable(&mut self, executable: String)
+//             -> Result<&mut Self, CommandBuilderError> {...}
+//     }
+//
+// Values given to the plain setter are checked by build() instead. Either way
+// the error is the `Invalid{Field}` variant of the builder's error type.
+
+use derive_builder::Builder;
+use std::error::Error;
+
+#[derive(Builder)]
+pub struct Command {
+    #[builder(try_setter, validate = "not_empty")]
+    executable: String,
+    #[builder(try_setter, validate = "relative", setter(into))]
+    current_dir: Option<String>,
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+struct Port {
+    #[builder(try_setter, validate = "non_zero")]
+    number: u16,
+}
+
+fn not_empty(value: &String) -> Result<(), &'static str> {
+    if value.is_empty() {
+        Err("must not be empty")
+    } else {
+        Ok(())
+    }
+}
+
+fn relative(value: &String) -> Result<(), String> {
+    if value.starts_with('/') {
+        Err(format!("{} is not a relative path", value))
+    } else {
+        Ok(())
+    }
+}
+
+fn non_zero(value: &u16) -> Result<(), &'static str> {
+    if *value == 0 {
+        Err("port 0 is reserved")
+    } else {
+        Ok(())
+    }
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    let err = builder.try_executable(String::new()).err().unwrap();
+    assert!(matches!(err, CommandBuilderError::InvalidExecutable(_)));
+    assert_eq!(
+        err.to_string(),
+        "invalid value for field `executable`: must not be empty",
+    );
+    assert_eq!(err.source().unwrap().to_string(), "must not be empty");
+
+    builder
+        .try_executable("cargo".to_owned())
+        .unwrap()
+        .try_current_dir("target")
+        .unwrap();
+    let command = builder.build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.current_dir.unwrap(), "target");
+
+    let err = Command::builder()
+        .executable("cargo".to_owned())
+        .current_dir("/tmp")
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for field `current_dir`: /tmp is not a relative path",
+    );
+
+    assert!(Port::builder().try_number(0).is_err());
+    let port = Port::builder().try_number(8080).unwrap().build().unwrap();
+    assert_eq!(port.number, 8080);
+    assert!(Port::builder().number(0).build().is_err());
+}
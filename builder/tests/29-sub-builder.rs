@@ -0,0 +1,114 @@
+// A field whose type derives Builder itself can be marked
+// #[builder(sub_builder)]. In place of a setter, the outer builder then has an
+// accessor handing out the nested builder, which is created the first time it
+// is asked for:
+//
+//     impl ServiceBuilder {
+//         fn retry_policy(&mut self) -> &mut RetryPolicyBuilder {...}
+//     }
+//
+// The outer build() builds the nested builder as well. A nested builder that
+// was never touched is built as it is, so the nested struct's own defaults
+// apply. An error from the nested build() comes back as the `Nested{Field}`
+// variant of the outer error, with the field name in front of its message.
+//
+// The nested builder's type is the field's type name followed by `Builder`,
+// or can be given explicitly with #[builder(sub_builder = "...")]. It is built
+// whatever its build function is called and whichever pattern it uses; a
+// nested "owned" builder held by a builder which is not consumed by build()
+// is cloned to build it, which needs its fields to be Clone. A typestate
+// builder cannot be nested, see 40-typestate-sub-builder.rs, though a typestate
+// builder can hold other builders, as Deployment does below.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    #[builder(default = "100")]
+    backoff_ms: u64,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(name = "LimitsSpec")]
+pub struct Limits {
+    #[builder(default = "16")]
+    connections: usize,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(build_fn = "finish")]
+pub struct Timeouts {
+    #[builder(default = "30")]
+    connect_secs: u64,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Tls {
+    #[builder(default)]
+    verify: bool,
+}
+
+#[derive(Builder, Debug)]
+pub struct Service {
+    name: String,
+    #[builder(sub_builder)]
+    retry_policy: RetryPolicy,
+    #[builder(sub_builder = "LimitsSpec")]
+    limits: Limits,
+    #[builder(sub_builder)]
+    timeouts: Timeouts,
+    #[builder(sub_builder)]
+    tls: Tls,
+}
+
+#[derive(Builder, Debug)]
+#[builder(typestate)]
+pub struct Deployment {
+    region: String,
+    #[builder(sub_builder)]
+    service: Service,
+}
+
+fn main() {
+    let mut builder = Service::builder();
+    builder.name("api".to_owned());
+    builder.retry_policy().max_attempts(3);
+    let service = builder.build().unwrap();
+    assert_eq!(
+        service.retry_policy,
+        RetryPolicy {
+            max_attempts: 3,
+            backoff_ms: 100,
+        }
+    );
+    assert_eq!(service.limits, Limits { connections: 16 });
+    assert_eq!(service.timeouts, Timeouts { connect_secs: 30 });
+    assert_eq!(service.tls, Tls { verify: false });
+
+    let mut builder = Service::builder();
+    builder.name("api".to_owned());
+    builder.retry_policy().max_attempts(1);
+    builder.timeouts().connect_secs(5);
+    let tls = std::mem::take(builder.tls()).verify(true);
+    *builder.tls() = tls;
+    let service = builder.build().unwrap();
+    assert_eq!(service.timeouts, Timeouts { connect_secs: 5 });
+    assert_eq!(service.tls, Tls { verify: true });
+
+    let err = Service::builder().name("api".to_owned()).build().unwrap_err();
+    assert!(matches!(err, ServiceBuilderError::NestedRetryPolicy(_)));
+    assert_eq!(
+        err.to_string(),
+        "retry_policy: missing required field `max_attempts`",
+    );
+
+    let mut deployment = Deployment::builder().region("eu".to_owned());
+    deployment.service().name("api".to_owned());
+    let err = deployment.build().unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "service: retry_policy: missing required field `max_attempts`",
+    );
+}
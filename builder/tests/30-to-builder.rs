@@ -0,0 +1,98 @@
+// An existing value can be turned back into a builder, tweaked, and built
+// again. `to_builder(&self)` clones every field into a new builder, and
+// `From<Command> for CommandBuilder` does the same by moving them.
+//
+// Each field starts out as if its setter had been called with the value it
+// has, including `each` fields, to which further elements are appended. A
+// field with #[builder(setter(skip))] has no storage in the builder and is
+// defaulted again by build().
+//
+// A typestate builder made this way has every required field marked as set,
+// so build() can be called on it straight away.
+//
+// A struct with a `to_builder` method of its own can rename the generated one
+// with #[builder(to_builder = "...")], or leave it out with
+// #[builder(to_builder = false)]. The `From` impl is there either way.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(setter(skip))]
+    runs: u32,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(typestate)]
+pub struct Point<T> {
+    x: T,
+    y: T,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(to_builder = "edit")]
+pub struct Label {
+    text: String,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(to_builder = false)]
+pub struct Tag {
+    name: String,
+}
+
+impl Tag {
+    pub fn to_builder(&self) -> TagBuilder {
+        let mut builder = Tag::builder();
+        builder.name(self.name.to_uppercase());
+        builder
+    }
+}
+
+fn main() {
+    let command = Command {
+        executable: "cargo".to_owned(),
+        args: vec!["build".to_owned()],
+        current_dir: Some("..".to_owned()),
+        runs: 3,
+    };
+
+    let rebuilt = command
+        .to_builder()
+        .arg("--release".to_owned())
+        .executable("cross".to_owned())
+        .build()
+        .unwrap();
+    assert_eq!(
+        rebuilt,
+        Command {
+            executable: "cross".to_owned(),
+            args: vec!["build".to_owned(), "--release".to_owned()],
+            current_dir: Some("..".to_owned()),
+            runs: 0,
+        }
+    );
+
+    let mut builder = CommandBuilder::from(command);
+    builder.current_dir("/".to_owned());
+    assert_eq!(builder.build().unwrap().current_dir.unwrap(), "/");
+
+    let point = Point { x: 1, y: 2 };
+    assert_eq!(point.to_builder().build(), point);
+    assert_eq!(PointBuilder::from(Point { x: 1, y: 2 }).y(5).build(), Point { x: 1, y: 5 });
+
+    let label = Label {
+        text: "a".to_owned(),
+    };
+    assert_eq!(label.edit().build().unwrap(), label);
+
+    let tag = Tag {
+        name: "a".to_owned(),
+    };
+    assert_eq!(tag.to_builder().build().unwrap().name, "A");
+    assert_eq!(TagBuilder::from(tag).build().unwrap().name, "a");
+}
@@ -0,0 +1,93 @@
+// #[builder(no_std)] generates code which only refers to `core`, for use in
+// crates that cannot link `std`. The error type is the same enum as
+// elsewhere, except that the errors of validation functions are carried as
+// plain messages: they must convert into `&'static str`.
+//
+// Adding #[builder(alloc)] lets the generated code use the `alloc` crate,
+// which the crate using it must declare with `extern crate alloc`. Errors are
+// then boxed as usual, and sub-builders become available.
+//
+// This test links `std` for the test harness only. The crate root does not
+// see it, so any path into `std` in the generated code fails to resolve.
+
+#![no_std]
+
+extern crate alloc;
+
+mod host {
+    extern crate std;
+}
+
+use alloc::string::{String, ToString};
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(no_std, validate = "check_uart")]
+pub struct Uart {
+    baud: u32,
+    #[builder(try_setter, validate = "check_name")]
+    name: &'static str,
+    parity: Option<bool>,
+}
+
+fn check_uart(uart: &Uart) -> Result<(), &'static str> {
+    if uart.baud > 115_200 && uart.parity.is_some() {
+        Err("parity is not supported above 115200 baud")
+    } else {
+        Ok(())
+    }
+}
+
+fn check_name(name: &&'static str) -> Result<(), &'static str> {
+    if name.is_empty() {
+        Err("must not be empty")
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Builder, Debug)]
+#[builder(no_std, alloc)]
+pub struct Board {
+    label: String,
+    #[builder(sub_builder)]
+    uart: Uart,
+}
+
+fn main() {
+    let uart = Uart::builder()
+        .baud(9600)
+        .try_name("debug")
+        .unwrap()
+        .parity(true)
+        .build()
+        .unwrap();
+    assert_eq!(
+        uart,
+        Uart {
+            baud: 9600,
+            name: "debug",
+            parity: Some(true),
+        }
+    );
+
+    let err = Uart::builder().baud(9600).try_name("").err().unwrap();
+    assert!(matches!(err, UartBuilderError::InvalidName("must not be empty")));
+
+    let err = Uart::builder()
+        .baud(921_600)
+        .name("fast")
+        .parity(false)
+        .build()
+        .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "parity is not supported above 115200 baud",
+    );
+
+    let mut board = Board::builder();
+    board.label("devkit".to_string());
+    board.uart().name("console");
+    let err = board.build().unwrap_err();
+    assert_eq!(err.to_string(), "uart: missing required field `baud`");
+}
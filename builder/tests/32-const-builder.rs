@@ -0,0 +1,51 @@
+// Setters and build() are generated as `const fn` wherever the field types
+// allow it, so that a value can be assembled by the builder at compile time.
+//
+// A setter is const if it stores its argument as given, without setter(into),
+// into a field whose type is made of primitives, shared references and
+// Options of them, as overwriting such a value runs no destructor. build() is
+// const if every field is like that and either required or an Option, and
+// there is no validation function or custom error type involved. The
+// "immutable" pattern, which clones the builder, never is.
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug, PartialEq)]
+pub struct Uart {
+    baud: u32,
+    name: &'static str,
+    parity: Option<bool>,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(pattern = "owned")]
+pub struct Pin {
+    port: char,
+    number: u8,
+}
+
+const CONSOLE: Uart = {
+    let mut builder = Uart::builder();
+    builder.baud(115_200).name("console");
+    match builder.build() {
+        Ok(uart) => uart,
+        Err(_) => panic!("incomplete uart"),
+    }
+};
+
+const LED: Pin = match Pin::builder().port('B').number(7).build() {
+    Ok(pin) => pin,
+    Err(_) => panic!("incomplete pin"),
+};
+
+fn main() {
+    assert_eq!(
+        CONSOLE,
+        Uart {
+            baud: 115_200,
+            name: "console",
+            parity: None,
+        }
+    );
+    assert_eq!(LED, Pin { port: 'B', number: 7 });
+}
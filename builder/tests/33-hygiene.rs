@@ -0,0 +1,132 @@
+// The same guarantee as test 09, across everything the derive can generate:
+// no expansion depends on the caller's prelude names or on names the caller
+// is free to use for items of their own. Every path is absolute, and the
+// variables generated code binds internally cannot collide with a constant or
+// unit struct of the caller's.
+
+#![allow(non_upper_case_globals, dead_code)]
+
+use derive_builder::Builder;
+
+type Option = ();
+type Some = ();
+type None = ();
+type Result = ();
+type Box = ();
+type Ok = ();
+type Err = ();
+type Vec = ();
+type String = ();
+type Into = ();
+type From = ();
+type Default = ();
+type Clone = ();
+type Error = ();
+type Extend = ();
+type PhantomData = ();
+type Display = ();
+
+mod core {}
+mod std {}
+mod alloc {}
+
+const value: () = ();
+const err: () = ();
+const f: () = ();
+const other: () = ();
+
+#[derive(Builder)]
+#[builder(validate = "check", merge)]
+pub struct Command {
+    #[builder(try_setter, validate = "not_empty", setter(into))]
+    executable: ::std::string::String,
+    #[builder(each = "arg")]
+    args: ::std::vec::Vec<::std::string::String>,
+    #[builder(each = "env")]
+    env: ::std::collections::HashMap<::std::string::String, ::std::string::String>,
+    current_dir: ::std::option::Option<::std::string::String>,
+    #[builder(default = "3")]
+    retries: u32,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Builder, ::std::clone::Clone)]
+pub struct Limits {
+    #[builder(default)]
+    memory: u64,
+}
+
+#[derive(Builder)]
+#[builder(pattern = "immutable", default)]
+pub struct Point {
+    x: i32,
+    y: i32,
+}
+
+impl ::std::default::Default for Point {
+    fn default() -> Self {
+        Point { x: 1, y: 2 }
+    }
+}
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Size {
+    width: u32,
+    #[builder(validate = "positive")]
+    height: u32,
+}
+
+#[derive(Builder)]
+pub enum Shape {
+    Circle { radius: f64 },
+    Empty,
+}
+
+fn check(_: &Command) -> ::std::result::Result<(), &'static str> {
+    ::std::result::Result::Ok(())
+}
+
+fn not_empty(_: &::std::string::String) -> ::std::result::Result<(), &'static str> {
+    ::std::result::Result::Ok(())
+}
+
+fn positive(height: &u32) -> ::std::result::Result<(), &'static str> {
+    if *height > 0 {
+        ::std::result::Result::Ok(())
+    } else {
+        ::std::result::Result::Err("must be positive")
+    }
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    builder
+        .try_executable("cargo")
+        .unwrap()
+        .arg("build".to_owned())
+        .env("RUST_LOG".to_owned(), "debug".to_owned());
+    builder.limits().memory(1024);
+
+    let mut overrides = Command::builder();
+    overrides.retries(5);
+    builder.merge(overrides);
+
+    let command = builder.build().unwrap();
+    assert_eq!(command.executable, "cargo");
+    assert_eq!(command.retries, 5);
+    assert_eq!(command.limits.memory, 1024);
+
+    let copy = command.to_builder().build().unwrap();
+    assert_eq!(copy.args, command.args);
+
+    let point = Point::builder().x(5).build().unwrap();
+    assert_eq!((point.x, point.y), (5, 2));
+
+    let size = Size::builder().width(3).height(4).build().unwrap();
+    assert_eq!((size.width, size.height), (3, 4));
+
+    let _ = Shape::circle_builder().radius(1.0).build().unwrap();
+    let _ = Shape::empty_builder().build().unwrap();
+}
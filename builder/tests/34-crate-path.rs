@@ -0,0 +1,65 @@
+// A crate re-exporting the derive for its users can't count on them naming
+// `core`, `alloc` or `std` the way the generated code expects. With
+// #[builder(crate = "path")] every such path in the expansion starts from
+// `path` instead: `path::core`, `path::alloc` and so on, as well as
+// `path::serde` for a builder deriving Deserialize.
+//
+// Here the crate only reaches `alloc` through a module of its own, so the
+// builder resolves only if the generated code goes through that module too.
+
+#![no_std]
+
+mod host {
+    extern crate std;
+}
+
+mod umbrella {
+    pub mod __private {
+        pub extern crate alloc;
+        pub use ::core;
+    }
+}
+
+use derive_builder::Builder;
+use umbrella::__private::alloc::string::{String, ToString};
+use umbrella::__private::alloc::vec::Vec;
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(crate = "umbrella::__private", no_std, alloc)]
+pub struct Packet {
+    #[builder(validate = "check_port")]
+    port: u16,
+    #[builder(each = "byte")]
+    payload: Vec<u8>,
+    #[builder(sub_builder)]
+    header: Header,
+}
+
+#[derive(Builder, Debug, PartialEq)]
+#[builder(crate = "umbrella::__private", no_std)]
+pub struct Header {
+    #[builder(default)]
+    version: u8,
+}
+
+fn check_port(port: &u16) -> Result<(), String> {
+    if *port == 0 {
+        Err("port 0 is reserved".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn main() {
+    let mut builder = Packet::builder();
+    builder.port(8080).byte(1).byte(2);
+    builder.header().version(4);
+    let packet = builder.build().unwrap();
+    assert_eq!(packet.payload, [1, 2]);
+    assert_eq!(packet.header, Header { version: 4 });
+
+    let mut builder = Packet::builder();
+    builder.port(0);
+    let err = builder.build().unwrap_err();
+    assert_eq!(err.to_string(), "invalid value for field `port`: port 0 is reserved");
+}
@@ -0,0 +1,98 @@
+// Everything public the derive generates is documented, so that crates
+// denying `missing_docs` can derive builders for their public types.
+//
+// Setters, `each` setters, `try_` setters and sub-builder accessors repeat the
+// doc comments of their field. The builder type itself lists which fields
+// build() needs and the value every other field takes when it is not set, and
+// build() says how it can fail.
+
+//! Builders for a process launcher.
+
+#![deny(missing_docs)]
+
+use derive_builder::Builder;
+
+/// A command to run.
+#[derive(Builder)]
+#[builder(validate = "check")]
+pub struct Command {
+    /// Program to run, looked up in `PATH`.
+    #[builder(try_setter, validate = "not_empty")]
+    pub executable: String,
+    /// Arguments passed to the program.
+    #[builder(each = "arg")]
+    pub args: Vec<String>,
+    /// Directory to run the program in.
+    pub current_dir: Option<String>,
+    /// How many times to retry a failed run.
+    #[builder(default = "3")]
+    pub retries: u32,
+    /// Resource limits of the process.
+    #[builder(sub_builder)]
+    pub limits: Limits,
+    tag: u8,
+}
+
+/// Resource limits.
+#[derive(Builder)]
+pub struct Limits {
+    /// Maximum memory, in bytes.
+    #[builder(default)]
+    pub memory: u64,
+}
+
+/// A size, known to be complete at compile time.
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Size {
+    /// Width in pixels.
+    pub width: u32,
+    /// Height in pixels.
+    #[builder(try_setter, validate = "positive")]
+    pub height: u32,
+}
+
+/// A shape.
+#[derive(Builder)]
+pub enum Shape {
+    /// A circle.
+    Circle {
+        /// Radius of the circle.
+        radius: f64,
+    },
+    /// Nothing.
+    Empty,
+}
+
+fn check(_: &Command) -> Result<(), String> {
+    Ok(())
+}
+
+fn not_empty(executable: &String) -> Result<(), String> {
+    if executable.is_empty() {
+        Err("must not be empty".to_owned())
+    } else {
+        Ok(())
+    }
+}
+
+fn positive(height: &u32) -> Result<(), String> {
+    if *height > 0 {
+        Ok(())
+    } else {
+        Err("must be positive".to_owned())
+    }
+}
+
+fn main() {
+    let mut command = Command::builder();
+    command.try_executable("ls".to_owned()).unwrap().tag(0);
+    command.limits().memory(1 << 20);
+    let command = command.build().unwrap();
+    assert_eq!(command.retries, 3);
+
+    let size = Size::builder().width(4).try_height(3).unwrap().build().unwrap();
+    assert_eq!(size.width * size.height, 12);
+
+    let _ = Shape::empty_builder().build().unwrap();
+}
@@ -0,0 +1,77 @@
+// With #[builder(getters)] the builder can be asked what it holds so far.
+// Every field gets a `get_` method returning the value it was set to, if any;
+// a setter already takes the field's own name. For an `Option<T>` field the
+// getter returns `Option<&T>`, for a sub-builder it returns the nested
+// builder.
+//
+// is_complete() tells whether every required field has been set, and
+// missing_fields() names the ones that have not, in the order the struct
+// declares them. The names match those reported by the error's
+// missing_field().
+
+use derive_builder::Builder;
+
+#[derive(Builder, Debug)]
+#[builder(getters)]
+pub struct Command {
+    executable: String,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    current_dir: Option<String>,
+    #[builder(default = "3")]
+    retries: u32,
+    r#type: u8,
+    #[builder(sub_builder)]
+    limits: Limits,
+}
+
+#[derive(Builder, Debug)]
+#[builder(getters)]
+pub struct Limits {
+    #[builder(default)]
+    memory: u64,
+}
+
+#[derive(Builder, Debug)]
+#[builder(getters, pattern = "owned")]
+pub struct Point {
+    x: i32,
+    y: Option<i32>,
+}
+
+fn main() {
+    let mut builder = Command::builder();
+    assert!(!builder.is_complete());
+    assert_eq!(builder.missing_fields(), ["executable", "type"]);
+    assert_eq!(builder.get_executable(), None);
+    assert_eq!(builder.get_current_dir(), None);
+    assert!(builder.get_limits().is_none());
+
+    builder.executable("cargo".to_owned()).arg("build".to_owned());
+    assert_eq!(builder.get_executable().map(String::as_str), Some("cargo"));
+    assert_eq!(builder.get_args(), Some(&vec!["build".to_owned()]));
+    assert_eq!(builder.get_retries(), None);
+    assert_eq!(builder.missing_fields(), ["type"]);
+
+    builder.current_dir("/tmp".to_owned()).r#type(1);
+    assert_eq!(builder.get_current_dir().map(String::as_str), Some("/tmp"));
+    assert_eq!(builder.get_type(), Some(&1));
+    assert!(builder.is_complete());
+    assert!(builder.missing_fields().is_empty());
+
+    builder.limits().memory(64);
+    assert!(builder.get_limits().is_some());
+
+    let command = builder.build().unwrap();
+    assert_eq!(command.retries, 3);
+
+    let mut limits = Limits::builder();
+    assert!(limits.is_complete());
+    assert!(limits.missing_fields().is_empty());
+    assert_eq!(limits.memory(8).get_memory(), Some(&8));
+
+    let point = Point::builder().y(2);
+    assert_eq!(point.missing_fields(), ["x"]);
+    let point = point.x(1);
+    assert_eq!((point.get_x(), point.get_y()), (Some(&1), Some(&2)));
+}
@@ -0,0 +1,55 @@
+// Every mistake in the #[builder] attributes is reported by the same
+// compilation, each pointing at the key it is about, rather than one error per
+// attempt to build.
+//
+// A misspelled key comes with the closest key the attribute accepts, or the
+// list of accepted keys if none is close. A key given twice is an error even
+// when both values agree, as are keys which cannot be used together and keys
+// which would have no effect where they are given, rather than being silently
+// dropped.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(patern = "owned", typestate)]
+#[builder(typestate)]
+pub struct Command {
+    #[builder(each = "arg", setter(skip))]
+    args: Vec<String>,
+    #[builder(defualt, setter(int))]
+    env: Vec<String>,
+    #[builder(name = "dir", name = "cwd")]
+    current_dir: Option<String>,
+    #[builder(colour = "red", try_setter)]
+    retries: u32,
+}
+
+#[derive(Builder)]
+pub struct Limits {
+    #[builder(default)]
+    connections: usize,
+}
+
+#[derive(Builder)]
+pub struct Task {
+    #[builder(setter(skip, into))]
+    log: Vec<String>,
+    #[builder(setter(strip_option = false))]
+    retries: u32,
+    #[builder(sub_builder, setter(into))]
+    limits: Limits,
+}
+
+#[derive(Builder)]
+#[builder(error = "String", typestate)]
+pub struct Point {
+    x: i32,
+}
+
+#[derive(Builder)]
+#[builder(to_builder = "edit")]
+pub enum Shape {
+    Circle { radius: u32 },
+}
+
+fn main() {}
@@ -0,0 +1,100 @@
+// Some defaults cannot be computed on the spot. #[builder(default_with =
+// "path")] names an async function producing the value of a field left unset,
+// and the builder gets a build_async() method which awaits it. build() is still
+// there, but cannot wait: it reports such a field as missing unless it was set,
+// and so do is_complete() and missing_fields() with #[builder(getters)].
+//
+// #[builder(try_default = "path")] names a function returning a Result, for a
+// default which may fail. Its error comes out of build() through the
+// `Default{Field}` variant of the builder's error type.
+
+use derive_builder::Builder;
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Pool {
+    size: usize,
+}
+
+#[derive(Builder, Debug)]
+#[builder(getters)]
+pub struct Client {
+    url: String,
+    #[builder(default_with = "connect")]
+    pool: Pool,
+    #[builder(try_default = "timeout_from_env")]
+    timeout: u32,
+}
+
+async fn connect() -> Pool {
+    ready().await;
+    Pool { size: 8 }
+}
+
+fn timeout_from_env() -> Result<u32, std::num::ParseIntError> {
+    std::env::var("CLIENT_TIMEOUT")
+        .unwrap_or_else(|_| "30".to_owned())
+        .parse()
+}
+
+// Yields once before completing, as real I/O would.
+async fn ready() {
+    let mut polled = false;
+    std::future::poll_fn(|cx| {
+        if polled {
+            Poll::Ready(())
+        } else {
+            polled = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    })
+    .await
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut cx = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            return output;
+        }
+    }
+}
+
+fn main() {
+    let mut builder = Client::builder();
+    builder.url("https://example.com".to_owned());
+
+    let client = block_on(builder.build_async()).unwrap();
+    assert_eq!(client.pool, Pool { size: 8 });
+    assert_eq!(client.timeout, 30);
+
+    assert!(!builder.is_complete());
+    assert_eq!(builder.missing_fields(), ["pool"]);
+    let err = builder.build().unwrap_err();
+    assert!(matches!(err, ClientBuilderError::MissingPool));
+    assert_eq!(err.missing_field(), Some("pool"));
+    assert_eq!(
+        err.to_string(),
+        "field `pool` was not set and has an async default; call build_async()",
+    );
+
+    builder.pool(Pool { size: 1 });
+    assert!(builder.is_complete());
+    let client = builder.build().unwrap();
+    assert_eq!(client.pool, Pool { size: 1 });
+
+    std::env::set_var("CLIENT_TIMEOUT", "soon");
+    let err = block_on(builder.build_async()).unwrap_err();
+    assert!(matches!(err, ClientBuilderError::DefaultTimeout(_)));
+    assert_eq!(
+        err.to_string(),
+        "could not produce a default for field `timeout`: invalid digit found in string",
+    );
+
+    builder.timeout(5);
+    assert_eq!(builder.build().unwrap().timeout, 5);
+}
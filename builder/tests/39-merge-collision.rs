@@ -0,0 +1,56 @@
+// A setter named the same as another method of the builder is reported at
+// the field, rather than failing on two methods with the same name. This
+// covers `merge` with #[builder(merge)], the methods of #[builder(getters)],
+// the build method and its async counterpart, `try_` setters, and two setters
+// with the same name. Renaming the setter with #[builder(name = "...")]
+// resolves it.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(merge)]
+pub struct Strategy {
+    merge: bool,
+    #[builder(each = "merge")]
+    steps: Vec<String>,
+    #[builder(name = "merge_mode")]
+    mode: String,
+}
+
+#[derive(Builder)]
+#[builder(getters)]
+pub struct Job {
+    is_complete: bool,
+    missing_fields: Vec<String>,
+    name: String,
+    get_name: String,
+}
+
+#[derive(Builder)]
+#[builder(build_fn = "finish")]
+pub struct Step {
+    finish: u32,
+    #[builder(default_with = "load")]
+    pool: u32,
+    finish_async: bool,
+}
+
+async fn load() -> u32 {
+    0
+}
+
+#[derive(Builder)]
+pub struct Port {
+    #[builder(validate = "check", try_setter)]
+    number: u16,
+    try_number: u16,
+    #[builder(each = "arg")]
+    args: Vec<String>,
+    arg: String,
+}
+
+fn check(_: &u16) -> Result<(), String> {
+    Ok(())
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+// A typestate builder cannot be nested with #[builder(sub_builder)]: its
+// setters change its type, so there is no single builder type for the
+// enclosing builder to hold and hand out. Using one is reported at the field,
+// which can instead be set to a value built separately.
+
+use derive_builder::Builder;
+
+#[derive(Builder)]
+#[builder(typestate)]
+pub struct Endpoint {
+    host: String,
+    port: u16,
+}
+
+#[derive(Builder)]
+pub struct Service {
+    name: String,
+    #[builder(sub_builder)]
+    endpoint: Endpoint,
+}
+
+fn main() {}
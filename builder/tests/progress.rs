@@ -1,13 +1,45 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse.rs");
-    //t.pass("tests/02-create-builder.rs");
-    //t.pass("tests/03-call-setters.rs");
-    //t.pass("tests/04-call-build.rs");
-    //t.pass("tests/05-method-chaining.rs");
-    //t.pass("tests/06-optional-field.rs");
-    //t.pass("tests/07-repeated-field.rs");
-    //t.compile_fail("tests/08-unrecognized-attribute.rs");
-    //t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-create-builder.rs");
+    t.pass("tests/03-call-setters.rs");
+    t.pass("tests/04-call-build.rs");
+    t.pass("tests/05-method-chaining.rs");
+    t.pass("tests/06-optional-field.rs");
+    t.pass("tests/07-repeated-field.rs");
+    t.compile_fail("tests/08-unrecognized-attribute.rs");
+    t.pass("tests/09-redefined-prelude-types.rs");
+    t.pass("tests/10-typestate.rs");
+    t.compile_fail("tests/11-typestate-missing-field.rs");
+    t.pass("tests/12-default.rs");
+    t.pass("tests/13-validate.rs");
+    t.pass("tests/14-error-type.rs");
+    t.pass("tests/15-generics.rs");
+    t.compile_fail("tests/16-generics-bounds.rs");
+    t.pass("tests/17-setter-options.rs");
+    t.compile_fail("tests/18-skipped-setter.rs");
+    t.pass("tests/19-pattern.rs");
+    t.compile_fail("tests/20-typestate-pattern.rs");
+    t.pass("tests/21-each-collections.rs");
+    t.pass("tests/22-tuple-struct.rs");
+    t.pass("tests/23-enum.rs");
+    t.pass("tests/24-builder-naming.rs");
+    t.compile_fail("tests/25-builder-visibility.rs");
+    t.pass("tests/26-merge.rs");
+    t.pass("tests/28-field-validate.rs");
+    t.pass("tests/29-sub-builder.rs");
+    t.pass("tests/30-to-builder.rs");
+    t.pass("tests/31-no-std.rs");
+    t.pass("tests/32-const-builder.rs");
+    t.pass("tests/33-hygiene.rs");
+    t.pass("tests/34-crate-path.rs");
+    t.pass("tests/35-generated-docs.rs");
+    t.pass("tests/36-getters.rs");
+    t.compile_fail("tests/37-attribute-errors.rs");
+    t.pass("tests/38-async-default.rs");
+    t.compile_fail("tests/39-merge-collision.rs");
+    t.compile_fail("tests/40-typestate-sub-builder.rs");
+    #[cfg(feature = "serde")]
+    t.pass("tests/27-serde.rs");
 }
@@ -0,0 +1,212 @@
+use proc_macro2::TokenTree;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{
+    Attribute, Error, Expr, ExprLit, Lit, LitStr, Meta, Path, Result, Token, WherePredicate,
+};
+
+/// Options given by `#[debug(...)]` on the struct or enum itself.
+#[derive(Default)]
+pub struct ContainerAttrs {
+    /// Predicates for the impl's where clause, in place of the inferred
+    /// bounds.
+    pub bound: Option<Punctuated<WherePredicate, Token![,]>>,
+    /// End the output with `..`, as `finish_non_exhaustive()` does.
+    pub non_exhaustive: bool,
+}
+
+/// Options given by `#[debug...]` on a single field.
+#[derive(Default)]
+pub struct FieldAttrs {
+    /// `#[debug = "..."]`: format string applied to the field's value, in
+    /// place of `{:?}`.
+    pub format: Option<LitStr>,
+    /// `#[debug(skip)]`: leave the field out of the output.
+    pub skip: bool,
+    /// `#[debug(redact)]` or `#[debug(redact = "...")]`: text printed in
+    /// place of the field's value.
+    pub redact: Option<LitStr>,
+    /// `#[debug(with = "path")]`: function formatting the field's value in
+    /// place of its `Debug` impl.
+    pub with: Option<Path>,
+}
+
+/// Placeholder printed for a field with a bare `#[debug(redact)]`.
+const REDACTED: &str = "[redacted]";
+
+pub fn container(attrs: &[Attribute]) -> Result<ContainerAttrs> {
+    let mut container = ContainerAttrs::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("debug")) {
+        if let Meta::List(_) = attr.meta {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("bound") {
+                    let bound: LitStr = meta.value()?.parse()?;
+                    container.bound = Some(bound.parse_with(Punctuated::parse_terminated)?);
+                    Ok(())
+                } else if meta.path.is_ident("non_exhaustive") {
+                    container.non_exhaustive = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `debug(bound = \"...\")` or `debug(non_exhaustive)`"))
+                }
+            })?;
+        } else {
+            return Err(Error::new_spanned(
+                &attr.meta,
+                "expected `debug(bound = \"...\")` or `debug(non_exhaustive)`",
+            ));
+        }
+    }
+
+    Ok(container)
+}
+
+pub fn field(attrs: &[Attribute]) -> Result<FieldAttrs> {
+    let mut field = FieldAttrs::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("debug")) {
+        match &attr.meta {
+            Meta::NameValue(meta) => match &meta.value {
+                Expr::Lit(ExprLit {
+                    lit: Lit::Str(format),
+                    ..
+                }) => field.format = Some(format.clone()),
+                value => {
+                    return Err(Error::new_spanned(
+                        value,
+                        "expected a format string, as in `#[debug = \"{:?}\"]`",
+                    ))
+                }
+            },
+            Meta::List(_) => attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("skip") {
+                    field.skip = true;
+                    Ok(())
+                } else if meta.path.is_ident("redact") {
+                    field.redact = Some(if meta.input.peek(Token![=]) {
+                        meta.value()?.parse()?
+                    } else {
+                        LitStr::new(REDACTED, meta.path.span())
+                    });
+                    Ok(())
+                } else if meta.path.is_ident("with") {
+                    let path: LitStr = meta.value()?.parse()?;
+                    field.with = Some(path.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error(
+                        "expected one of `debug(skip)`, `debug(redact)`, `debug(redact = \"...\")` or `debug(with = \"...\")`",
+                    ))
+                }
+            })?,
+            Meta::Path(path) => {
+                return Err(Error::new_spanned(
+                    path,
+                    "expected `debug = \"...\"`, `debug(skip)`, `debug(redact)` or `debug(with = \"...\")`",
+                ))
+            }
+        }
+    }
+
+    let given = [
+        field.format.is_some(),
+        field.skip,
+        field.redact.is_some(),
+        field.with.is_some(),
+    ];
+    if given.iter().filter(|given| **given).count() > 1 {
+        let attr = attrs
+            .iter()
+            .rev()
+            .find(|attr| attr.path().is_ident("debug"));
+        return Err(Error::new_spanned(
+            attr,
+            "only one of `debug = \"...\"`, `debug(skip)`, `debug(redact)` and `debug(with = \"...\")` can be given",
+        ));
+    }
+
+    Ok(field)
+}
+
+/// Rejects `#[debug]` on an enum variant, which has no options of its own.
+pub fn variant(attrs: &[Attribute]) -> Result<()> {
+    unsupported(attrs, "debug", "enum variants")
+}
+
+/// Options given by `#[display(...)]` on a struct or enum.
+#[derive(Default)]
+pub struct DisplayAttrs {
+    /// `#[display("...")]`: the format string of a struct.
+    pub format: Option<LitStr>,
+    /// `#[display(bound = "...")]`: predicates for the impl's where clause, in
+    /// place of the inferred bounds.
+    pub bound: Option<Punctuated<WherePredicate, Token![,]>>,
+}
+
+/// Parses the `#[display(...)]` attributes of a struct or enum.
+pub fn display(attrs: &[Attribute]) -> Result<DisplayAttrs> {
+    parse_display(attrs, true)
+}
+
+/// The format string given by `#[display("...")]` on an enum variant.
+pub fn display_variant(attrs: &[Attribute]) -> Result<Option<LitStr>> {
+    Ok(parse_display(attrs, false)?.format)
+}
+
+fn parse_display(attrs: &[Attribute], bound_allowed: bool) -> Result<DisplayAttrs> {
+    let mut display = DisplayAttrs::default();
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("display")) {
+        let list = match &attr.meta {
+            Meta::List(list) => list,
+            meta => {
+                return Err(Error::new_spanned(
+                    meta,
+                    "expected a format string, as in `#[display(\"{0}\")]`",
+                ))
+            }
+        };
+        if let Some(TokenTree::Literal(_)) = list.tokens.clone().into_iter().next() {
+            if display.format.is_some() {
+                return Err(Error::new_spanned(attr, "duplicate format string"));
+            }
+            display.format = Some(list.parse_args()?);
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("bound") {
+                if !bound_allowed {
+                    return Err(meta.error(
+                        "`display(bound = \"...\")` goes on the enum rather than its variants",
+                    ));
+                }
+                let bound: LitStr = meta.value()?.parse()?;
+                display.bound = Some(bound.parse_with(Punctuated::parse_terminated)?);
+                Ok(())
+            } else {
+                Err(meta.error(
+                    "expected a format string, as in `#[display(\"{0}\")]`, or `display(bound = \"...\")`",
+                ))
+            }
+        })?;
+    }
+
+    Ok(display)
+}
+
+/// Rejects `#[display]` on a field, which the format string refers to
+/// instead.
+pub fn display_field(attrs: &[Attribute]) -> Result<()> {
+    unsupported(attrs, "display", "fields")
+}
+
+fn unsupported(attrs: &[Attribute], name: &str, place: &str) -> Result<()> {
+    match attrs.iter().find(|attr| attr.path().is_ident(name)) {
+        Some(attr) => Err(Error::new_spanned(
+            attr,
+            format!("`#[{}]` is not supported on {}", name, place),
+        )),
+        None => Ok(()),
+    }
+}
@@ -0,0 +1,281 @@
+//! Inference of the where clause of the generated impls.
+//!
+//! The type of every formatted field is walked down to the parts which
+//! depend on a type parameter, and each of those gets a bound on the
+//! formatting trait, `Debug` or `Display` for instance:
+//!
+//! - A parameter `T` by itself is bounded as `T`, including as an argument of
+//!   another type such as `Rc<RefCell<T>>`, whose impls of the formatting
+//!   traits only ask the same of their arguments.
+//! - An associated type of a parameter, `T::Value` or `<T as Trait>::Value`,
+//!   bounds that path rather than `T`, which need not implement the trait
+//!   itself.
+//! - `PhantomData`, raw pointers and function pointers are formatted the same
+//!   whatever their parameters, and add no bound.
+//!
+//! A parameter inside a trait object, as in `Box<dyn Fn(T)>`, or inside a
+//! macro, is out of reach: whether the field can be formatted does not follow
+//! from bounds on the parameter, so the field is reported rather than guessed
+//! at.
+//!
+//! Bounds are only added once, and not at all when the type's own generics
+//! already state them.
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::ToTokens;
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_quote, Error, Field, GenericArgument, Generics, Ident, Path, PathArguments, Result,
+    Token, Type, TypeParamBound, TypePath, WherePredicate,
+};
+
+/// Bounds needed to format each field with the `core::fmt` trait it is
+/// paired with. When a field is out of reach, the error about it ends with
+/// `hint`, if any.
+pub fn infer_each(
+    generics: &Generics,
+    formatted: &[(Ident, &Field)],
+    hint: Option<&str>,
+) -> Result<Vec<WherePredicate>> {
+    // One pass per trait, in order of first use.
+    let mut traits: Vec<&Ident> = Vec::new();
+    for (format_trait, _) in formatted {
+        if !traits.contains(&format_trait) {
+            traits.push(format_trait);
+        }
+    }
+    let mut predicates = Vec::new();
+    for format_trait in traits {
+        let fields = formatted
+            .iter()
+            .filter(|(other, _)| other == format_trait)
+            .map(|(_, field)| *field);
+        let format_trait: Path = parse_quote!(::core::fmt::#format_trait);
+        predicates.extend(infer(generics, fields, &format_trait, hint)?);
+    }
+    Ok(predicates)
+}
+
+/// Bounds on `format_trait` needed to format the values of `fields`. When a
+/// field is out of reach, the error about it ends with `hint`, if any.
+fn infer<'a, I>(
+    generics: &Generics,
+    fields: I,
+    format_trait: &Path,
+    hint: Option<&str>,
+) -> Result<Vec<WherePredicate>>
+where
+    I: IntoIterator<Item = &'a Field>,
+{
+    let mut usage = Usage {
+        params: generics.type_params().map(|param| &param.ident).collect(),
+        direct: Vec::new(),
+        associated: Vec::new(),
+    };
+    let mut errors: Option<Error> = None;
+    for field in fields {
+        if let Err(Unreachable { param, inside }) = usage.walk(&field.ty) {
+            let trait_name = &format_trait.segments.last().unwrap().ident;
+            let mut message = format!(
+                "cannot infer the bounds needed to format this field with `{}`, as `{}` appears inside {}",
+                trait_name, param, inside,
+            );
+            if let Some(hint) = hint {
+                message.push_str("; ");
+                message.push_str(hint);
+            }
+            let error = Error::new_spanned(&field.ty, message);
+            match &mut errors {
+                Some(errors) => errors.combine(error),
+                None => errors = Some(error),
+            }
+        }
+    }
+    if let Some(errors) = errors {
+        return Err(errors);
+    }
+
+    // Parameters in declaration order, for a stable where clause.
+    let direct = usage
+        .params
+        .iter()
+        .filter(|param| usage.direct.contains(param))
+        .map(|param| -> Type { parse_quote!(#param) });
+    let associated = usage
+        .associated
+        .iter()
+        .map(|path| Type::Path((*path).clone()));
+    let stated = stated(generics, format_trait);
+    Ok(direct
+        .chain(associated)
+        .filter(|ty| !stated.contains(&key(ty)))
+        .map(|ty| parse_quote!(#ty: #format_trait))
+        .collect())
+}
+
+/// How the type parameters appear in the formatted fields.
+struct Usage<'a> {
+    params: Vec<&'a Ident>,
+    /// Parameters formatted by themselves.
+    direct: Vec<&'a Ident>,
+    /// Paths to associated types of parameters, qualified or not.
+    associated: Vec<&'a TypePath>,
+}
+
+/// Where a parameter was found out of reach of any bound.
+struct Unreachable<'a> {
+    param: &'a Ident,
+    inside: &'static str,
+}
+
+impl<'a> Usage<'a> {
+    fn walk(&mut self, ty: &'a Type) -> std::result::Result<(), Unreachable<'a>> {
+        match ty {
+            Type::Path(ty) => self.walk_path(ty),
+            Type::Reference(ty) => self.walk(&ty.elem),
+            Type::Array(ty) => self.walk(&ty.elem),
+            Type::Slice(ty) => self.walk(&ty.elem),
+            Type::Paren(ty) => self.walk(&ty.elem),
+            Type::Group(ty) => self.walk(&ty.elem),
+            Type::Tuple(ty) => ty.elems.iter().try_for_each(|elem| self.walk(elem)),
+            Type::Ptr(_) | Type::BareFn(_) | Type::Never(_) => Ok(()),
+            Type::TraitObject(_) => self.unreachable(ty, "a trait object"),
+            Type::ImplTrait(_) => self.unreachable(ty, "an `impl Trait` type"),
+            Type::Macro(ty) => self.unreachable(&ty.mac.tokens, "a macro"),
+            _ => self.unreachable(ty, "a type that cannot be looked into"),
+        }
+    }
+
+    fn walk_path(&mut self, ty: &'a TypePath) -> std::result::Result<(), Unreachable<'a>> {
+        if ty.qself.is_some() {
+            if self.mentioned(ty).is_some() {
+                self.associate(ty);
+            }
+            return Ok(());
+        }
+
+        let segments = &ty.path.segments;
+        if ty.path.leading_colon.is_none() {
+            if let Some(param) = self.param(&segments[0].ident) {
+                if segments.len() == 1 {
+                    if !self.direct.contains(&param) {
+                        self.direct.push(param);
+                    }
+                } else {
+                    self.associate(ty);
+                }
+                return Ok(());
+            }
+        }
+        if segments
+            .last()
+            .is_some_and(|last| last.ident == "PhantomData")
+        {
+            return Ok(());
+        }
+
+        for segment in segments {
+            match &segment.arguments {
+                PathArguments::None => {}
+                PathArguments::AngleBracketed(arguments) => {
+                    for argument in &arguments.args {
+                        match argument {
+                            GenericArgument::Type(ty) => self.walk(ty)?,
+                            GenericArgument::AssocType(assoc) => self.walk(&assoc.ty)?,
+                            _ => {}
+                        }
+                    }
+                }
+                // A bare trait object, as in `Box<Fn(T)>`.
+                PathArguments::Parenthesized(arguments) => {
+                    self.unreachable(arguments, "a trait object")?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Records a bound on the associated type `ty`, once.
+    fn associate(&mut self, ty: &'a TypePath) {
+        let ty_key = key(ty);
+        if !self.associated.iter().any(|path| key(path) == ty_key) {
+            self.associated.push(ty);
+        }
+    }
+
+    /// Fails if `tokens` mention a parameter.
+    fn unreachable<T: ToTokens>(
+        &self,
+        tokens: T,
+        inside: &'static str,
+    ) -> std::result::Result<(), Unreachable<'a>> {
+        match self.mentioned(tokens) {
+            Some(param) => Err(Unreachable { param, inside }),
+            None => Ok(()),
+        }
+    }
+
+    /// The first parameter named in `tokens`, if any.
+    fn mentioned<T: ToTokens>(&self, tokens: T) -> Option<&'a Ident> {
+        fn find<'a>(usage: &Usage<'a>, tokens: TokenStream) -> Option<&'a Ident> {
+            tokens.into_iter().find_map(|token| match token {
+                TokenTree::Ident(ident) => usage.param(&ident),
+                TokenTree::Group(group) => find(usage, group.stream()),
+                _ => None,
+            })
+        }
+        find(self, tokens.into_token_stream())
+    }
+
+    fn param(&self, ident: &Ident) -> Option<&'a Ident> {
+        self.params.iter().copied().find(|param| ident == *param)
+    }
+}
+
+/// The types which the generics already bound by `format_trait`, as keys.
+fn stated(generics: &Generics, format_trait: &Path) -> Vec<String> {
+    let bounds_trait = |bounds: &Punctuated<TypeParamBound, Token![+]>| {
+        bounds
+            .iter()
+            .any(|bound| is_format_trait(bound, format_trait))
+    };
+    let params = generics
+        .type_params()
+        .filter(|param| bounds_trait(&param.bounds))
+        .map(|param| key(&param.ident));
+    let predicates = generics
+        .where_clause
+        .iter()
+        .flat_map(|where_clause| &where_clause.predicates)
+        .filter_map(|predicate| match predicate {
+            WherePredicate::Type(predicate) if bounds_trait(&predicate.bounds) => {
+                Some(key(&predicate.bounded_ty))
+            }
+            _ => None,
+        });
+    params.chain(predicates).collect()
+}
+
+/// Whether `bound` names `format_trait`, as the usual paths to it spell it.
+fn is_format_trait(bound: &TypeParamBound, format_trait: &Path) -> bool {
+    let path = match bound {
+        TypeParamBound::Trait(bound) => &bound.path,
+        _ => return false,
+    };
+    let name = &format_trait.segments.last().unwrap().ident;
+    let spelled = key(path);
+    let spelled = spelled.trim_start_matches("::");
+    [
+        name.to_string(),
+        format!("fmt::{}", name),
+        format!("core::fmt::{}", name),
+        format!("std::fmt::{}", name),
+    ]
+    .iter()
+    .any(|path| path == spelled)
+}
+
+/// A type or path as text, to compare them.
+fn key<T: ToTokens>(tokens: T) -> String {
+    tokens.into_token_stream().to_string().replace(' ', "")
+}
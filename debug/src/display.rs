@@ -0,0 +1,143 @@
+use crate::attr;
+use crate::bound;
+use crate::format::{self, Arg};
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Error, Field, Fields, Ident, LitStr, Result};
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let container = attr::display(&input.attrs)?;
+    let ident = &input.ident;
+
+    // Fields the format strings refer to, with the trait each is formatted
+    // with.
+    let mut formatted: Vec<(Ident, &Field)> = Vec::new();
+    let arms = match &input.data {
+        Data::Struct(data) => {
+            let format = required(container.format.clone(), ident.span(), "struct")?;
+            vec![arm(quote!(#ident), format, &data.fields, &mut formatted)?]
+        }
+        Data::Enum(data) => {
+            if let Some(format) = &container.format {
+                return Err(Error::new_spanned(
+                    format,
+                    "the format string of an enum goes on each of its variants",
+                ));
+            }
+            data.variants
+                .iter()
+                .map(|variant| {
+                    let format = required(
+                        attr::display_variant(&variant.attrs)?,
+                        variant.ident.span(),
+                        "variant",
+                    )?;
+                    let variant_ident = &variant.ident;
+                    arm(
+                        quote!(#ident::#variant_ident),
+                        format,
+                        &variant.fields,
+                        &mut formatted,
+                    )
+                })
+                .collect::<Result<_>>()?
+        }
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "derive(CustomDisplay) does not support unions",
+            ))
+        }
+    };
+
+    let predicates = match container.bound {
+        Some(bound) => bound.into_iter().collect(),
+        None => bound::infer_each(
+            &input.generics,
+            &formatted,
+            Some("give the bounds of the impl with `#[display(bound = \"...\")]`"),
+        )?,
+    };
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Display for #ident #ty_generics #where_clause {
+            fn fmt(&self, __f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                match *self {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
+
+/// The format string of a struct or variant, which must have one.
+fn required(format: Option<LitStr>, span: Span, what: &str) -> Result<LitStr> {
+    format.ok_or_else(|| {
+        Error::new(
+            span,
+            format!(
+                "missing `#[display(\"...\")]` on {} deriving CustomDisplay",
+                what,
+            ),
+        )
+    })
+}
+
+/// Match arm writing the struct or variant spelled `path` with `format`,
+/// binding by reference the fields it refers to. The fields formatted are
+/// added to `formatted`.
+fn arm<'a>(
+    path: TokenStream,
+    format: LitStr,
+    fields: &'a Fields,
+    formatted: &mut Vec<(Ident, &'a Field)>,
+) -> Result<TokenStream> {
+    for field in fields {
+        attr::display_field(&field.attrs)?;
+    }
+    let (format, args) = format::rewrite(&format, fields)?;
+
+    let binding = |index: usize| format_ident!("__self_{}", index);
+    let mut used: Vec<usize> = Vec::new();
+    for Arg {
+        index,
+        format_trait,
+    } in args
+    {
+        if let Some(format_trait) = format_trait {
+            formatted.push((format_trait, fields.iter().nth(index).unwrap()));
+        }
+        if !used.contains(&index) {
+            used.push(index);
+        }
+    }
+
+    let patterns = fields.iter().enumerate().map(|(i, field)| {
+        let pattern = if used.contains(&i) {
+            let binding = binding(i);
+            quote!(ref #binding)
+        } else {
+            quote!(_)
+        };
+        match &field.ident {
+            Some(ident) => quote!(#ident: #pattern),
+            None => pattern,
+        }
+    });
+    let pattern = match fields {
+        Fields::Named(_) => quote!(#path { #(#patterns),* }),
+        Fields::Unnamed(_) => quote!(#path(#(#patterns),*)),
+        Fields::Unit => path,
+    };
+    let named: Vec<Ident> = used.into_iter().map(binding).collect();
+
+    Ok(quote! {
+        #pattern => ::core::fmt::Formatter::write_fmt(
+            __f,
+            ::core::format_args!(#format, #(#named = #named),*),
+        )
+    })
+}
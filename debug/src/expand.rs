@@ -0,0 +1,185 @@
+use crate::attr::{self, ContainerAttrs};
+use crate::bound;
+use crate::format;
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::{Data, DeriveInput, Error, Field, Fields, Ident, LitStr, Result};
+
+pub fn derive(input: &DeriveInput) -> Result<TokenStream> {
+    let container = attr::container(&input.attrs)?;
+    let ident = &input.ident;
+
+    // Fields whose values are formatted, with the trait each is formatted
+    // with, which the inferred bounds are about.
+    let mut formatted: Vec<(Ident, &Field)> = Vec::new();
+    let mut uses_with = false;
+    let arms = match &input.data {
+        Data::Struct(data) => vec![arm(
+            &container,
+            quote!(#ident),
+            ident,
+            &data.fields,
+            &mut formatted,
+            &mut uses_with,
+        )?],
+        Data::Enum(data) => data
+            .variants
+            .iter()
+            .map(|variant| {
+                attr::variant(&variant.attrs)?;
+                let variant_ident = &variant.ident;
+                arm(
+                    &container,
+                    quote!(#ident::#variant_ident),
+                    variant_ident,
+                    &variant.fields,
+                    &mut formatted,
+                    &mut uses_with,
+                )
+            })
+            .collect::<Result<_>>()?,
+        Data::Union(_) => {
+            return Err(Error::new_spanned(
+                &input.ident,
+                "derive(CustomDebug) does not support unions",
+            ))
+        }
+    };
+
+    let predicates = match container.bound {
+        Some(bound) => bound.into_iter().collect(),
+        None => bound::infer_each(
+            &input.generics,
+            &formatted,
+            Some("give the bounds of the impl with `#[debug(bound = \"...\")]`"),
+        )?,
+    };
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(predicates);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+
+    // Fields printed by a function of the caller's need a value whose Debug
+    // impl calls it, to hand to the debug builders.
+    let with = if uses_with {
+        Some(quote! {
+            struct __DebugWith<'__a, __T: ?::core::marker::Sized>(
+                &'__a __T,
+                fn(&__T, &mut ::core::fmt::Formatter) -> ::core::fmt::Result,
+            );
+
+            impl<'__a, __T: ?::core::marker::Sized> ::core::fmt::Debug for __DebugWith<'__a, __T> {
+                fn fmt(&self, __f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                    (self.1)(self.0, __f)
+                }
+            }
+        })
+    } else {
+        None
+    };
+
+    Ok(quote! {
+        impl #impl_generics ::core::fmt::Debug for #ident #ty_generics #where_clause {
+            fn fmt(&self, __f: &mut ::core::fmt::Formatter) -> ::core::fmt::Result {
+                #with
+
+                match *self {
+                    #(#arms,)*
+                }
+            }
+        }
+    })
+}
+
+/// Match arm formatting a struct or variant spelled `path`, whose output
+/// starts with `name`, binding each field it prints by reference. The fields
+/// printed through a formatting trait are added to `formatted` with the
+/// traits their format calls, and `uses_with` is set if a field is printed by
+/// a function of the caller's.
+fn arm<'a>(
+    container: &ContainerAttrs,
+    path: TokenStream,
+    name: &Ident,
+    fields: &'a Fields,
+    formatted: &mut Vec<(Ident, &'a Field)>,
+    uses_with: &mut bool,
+) -> Result<TokenStream> {
+    let name = LitStr::new(&name.unraw().to_string(), name.span());
+
+    // The pattern binding each field, and the name and value printed for it
+    // unless it is skipped.
+    let mut patterns = Vec::new();
+    let mut printed = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let attrs = attr::field(&field.attrs)?;
+        let binding = format_ident!("__self_{}", i);
+        // Skipped and redacted fields are never looked at.
+        let pattern = if attrs.skip || attrs.redact.is_some() {
+            quote!(_)
+        } else {
+            quote!(ref #binding)
+        };
+        patterns.push(match &field.ident {
+            Some(ident) => quote!(#ident: #pattern),
+            None => pattern,
+        });
+        if attrs.skip {
+            continue;
+        }
+
+        let value = if let Some(format) = attrs.format {
+            for format_trait in format::traits(&format) {
+                formatted.push((format_trait, field));
+            }
+            quote!(&::core::format_args!(#format, #binding))
+        } else if let Some(placeholder) = attrs.redact {
+            quote!(&::core::format_args!("{}", #placeholder))
+        } else if let Some(with) = attrs.with {
+            *uses_with = true;
+            quote!(&__DebugWith(#binding, #with))
+        } else {
+            formatted.push((format_ident!("Debug"), field));
+            quote!(#binding)
+        };
+        let field_name = field
+            .ident
+            .as_ref()
+            .map(|ident| LitStr::new(&ident.unraw().to_string(), ident.span()));
+        printed.push((field_name, value));
+    }
+
+    let finish = if container.non_exhaustive {
+        quote!(finish_non_exhaustive)
+    } else {
+        quote!(finish)
+    };
+    Ok(match fields {
+        Fields::Named(_) => {
+            let names = printed.iter().map(|(name, _)| name);
+            let values = printed.iter().map(|(_, value)| value);
+            quote! {
+                #path { #(#patterns),* } => {
+                    ::core::fmt::Formatter::debug_struct(__f, #name)
+                        #(.field(#names, #values))*
+                        .#finish()
+                }
+            }
+        }
+        Fields::Unnamed(_) => {
+            let values = printed.iter().map(|(_, value)| value);
+            quote! {
+                #path(#(#patterns),*) => {
+                    ::core::fmt::Formatter::debug_tuple(__f, #name)
+                        #(.field(#values))*
+                        .#finish()
+                }
+            }
+        }
+        Fields::Unit if container.non_exhaustive => quote! {
+            #path => ::core::fmt::Formatter::debug_struct(__f, #name).finish_non_exhaustive()
+        },
+        Fields::Unit => quote! {
+            #path => ::core::fmt::Formatter::write_str(__f, #name)
+        },
+    })
+}
@@ -0,0 +1,215 @@
+//! Format strings of `#[display("...")]` and `#[debug = "..."]`.
+//!
+//! The string names fields the way `format!` names its arguments: `{path}`
+//! for a named field, `{0}` or `{}` for a tuple field, including as the
+//! `width$` or `precision$` of another placeholder. Each reference is
+//! rewritten to the binding of its field in the generated match arm, which is
+//! then passed to `format_args!` as a named argument, so that fields the
+//! string does not mention are never looked at.
+//!
+//! The string of `#[debug = "..."]` only ever formats its own field, and is
+//! only looked at for the traits its placeholders call.
+
+use proc_macro2::Span;
+use syn::ext::IdentExt;
+use syn::{Error, Fields, Ident, LitStr, Result};
+
+/// A field the format string refers to.
+pub struct Arg {
+    /// Position of the field in its struct or variant.
+    pub index: usize,
+    /// Name of the `core::fmt` trait the field is formatted with, `None` if it
+    /// is only used as a width or precision, or with `{:p}`, which need no
+    /// bound.
+    pub format_trait: Option<Ident>,
+}
+
+/// `format` with every field reference replaced by the binding of the field,
+/// and the fields referenced, in order of first use.
+pub fn rewrite(format: &LitStr, fields: &Fields) -> Result<(LitStr, Vec<Arg>)> {
+    let value = format.value();
+    let mut rewritten = String::with_capacity(value.len());
+    let mut args = Vec::new();
+    // Index of the field an empty `{}` refers to.
+    let mut next = 0;
+
+    for piece in pieces(&value) {
+        let (name, spec) = match piece {
+            Piece::Text(text) => {
+                rewritten.push_str(text);
+                continue;
+            }
+            Piece::Placeholder { name, spec } => (name, spec),
+        };
+
+        let index = if name.is_empty() {
+            next += 1;
+            field(format, fields, &(next - 1).to_string())?
+        } else {
+            field(format, fields, name)?
+        };
+        add(&mut args, index, format_trait(spec.unwrap_or("")));
+        rewritten.push_str(&format!("{{__self_{}", index));
+        let mut spec = match spec {
+            Some(spec) => spec,
+            None => {
+                rewritten.push('}');
+                continue;
+            }
+        };
+
+        // Rewrite the `name$` of a width or precision taken from a field.
+        rewritten.push(':');
+        while let Some(dollar) = spec.find('$') {
+            let start = spec[..dollar]
+                .rfind(|c: char| !c.is_alphanumeric() && c != '_')
+                .map_or(0, |i| i + 1);
+            rewritten.push_str(&spec[..start]);
+            let name = &spec[start..dollar];
+            if name.is_empty() {
+                // A `$` fill character.
+                rewritten.push('$');
+            } else {
+                let index = field(format, fields, name)?;
+                add(&mut args, index, None);
+                rewritten.push_str(&format!("__self_{}$", index));
+            }
+            spec = &spec[dollar + 1..];
+        }
+        rewritten.push_str(spec);
+        rewritten.push('}');
+    }
+
+    Ok((LitStr::new(&rewritten, format.span()), args))
+}
+
+/// Names of the traits which the placeholders of `format` call, once each, in
+/// order of first use.
+pub fn traits(format: &LitStr) -> Vec<Ident> {
+    let mut traits: Vec<Ident> = Vec::new();
+    for piece in pieces(&format.value()) {
+        if let Piece::Placeholder { spec, .. } = piece {
+            if let Some(format_trait) = format_trait(spec.unwrap_or("")) {
+                if !traits.contains(&format_trait) {
+                    traits.push(format_trait);
+                }
+            }
+        }
+    }
+    traits
+}
+
+/// A part of a format string.
+enum Piece<'a> {
+    /// Text printed as it is, including escaped braces.
+    Text(&'a str),
+    /// `{name:spec}`, with `spec` only if there is a colon.
+    Placeholder {
+        name: &'a str,
+        spec: Option<&'a str>,
+    },
+}
+
+/// Splits a format string into text and placeholders. Malformed strings are
+/// left as text, for format_args! to report.
+fn pieces(value: &str) -> Vec<Piece<'_>> {
+    let mut pieces = Vec::new();
+    let mut rest = value;
+    while let Some(i) = rest.find(['{', '}']) {
+        pieces.push(Piece::Text(&rest[..i]));
+        rest = &rest[i..];
+        if rest.starts_with("{{") || rest.starts_with('}') {
+            let len = if rest.starts_with("{{") || rest.starts_with("}}") {
+                2
+            } else {
+                1
+            };
+            pieces.push(Piece::Text(&rest[..len]));
+            rest = &rest[len..];
+            continue;
+        }
+
+        let end = match rest.find('}') {
+            Some(end) => end,
+            None => break,
+        };
+        let placeholder = &rest[1..end];
+        rest = &rest[end + 1..];
+        pieces.push(match placeholder.find(':') {
+            Some(colon) => Piece::Placeholder {
+                name: &placeholder[..colon],
+                spec: Some(&placeholder[colon + 1..]),
+            },
+            None => Piece::Placeholder {
+                name: placeholder,
+                spec: None,
+            },
+        });
+    }
+    pieces.push(Piece::Text(rest));
+    pieces
+}
+
+/// Position of the field called `name` in `fields`, which is its index for a
+/// tuple field.
+fn field(format: &LitStr, fields: &Fields, name: &str) -> Result<usize> {
+    let found = match fields {
+        Fields::Named(_) => fields.iter().position(|field| {
+            field
+                .ident
+                .as_ref()
+                .is_some_and(|ident| ident.unraw() == name)
+        }),
+        Fields::Unnamed(_) => name.parse().ok().filter(|index| *index < fields.len()),
+        Fields::Unit => None,
+    };
+    found.ok_or_else(|| {
+        let message = match fields {
+            Fields::Named(_) if name.parse::<usize>().is_ok() => format!(
+                "format string refers to field `{}`, but fields with names must be referred to by name",
+                name,
+            ),
+            Fields::Unnamed(_) if name.parse::<usize>().is_err() => format!(
+                "format string refers to field `{}`, but tuple fields must be referred to by index, as in `{{0}}`",
+                name,
+            ),
+            Fields::Unit => format!("format string refers to field `{}`, but there are no fields", name),
+            _ => format!("format string refers to field `{}`, which does not exist", name),
+        };
+        Error::new(format.span(), message)
+    })
+}
+
+/// Records that the field at `index` is formatted with `format_trait`.
+fn add(args: &mut Vec<Arg>, index: usize, format_trait: Option<Ident>) {
+    let seen = args
+        .iter()
+        .any(|arg| arg.index == index && arg.format_trait == format_trait);
+    if !seen {
+        args.push(Arg {
+            index,
+            format_trait,
+        });
+    }
+}
+
+/// Name of the trait which the format `spec` of a placeholder calls, from
+/// its type.
+fn format_trait(spec: &str) -> Option<Ident> {
+    let name = if spec.ends_with('?') {
+        "Debug"
+    } else {
+        match spec.chars().last() {
+            Some('x') => "LowerHex",
+            Some('X') => "UpperHex",
+            Some('o') => "Octal",
+            Some('b') => "Binary",
+            Some('e') => "LowerExp",
+            Some('E') => "UpperExp",
+            // References are `Pointer` whatever they point to.
+            Some('p') => return None,
+            _ => "Display",
+        }
+    };
+    Some(Ident::new(name, Span::call_site()))
+}
@@ -0,0 +1,70 @@
+// Besides structs with named fields, the derive handles tuple structs, unit
+// structs and enums, printing each the way the standard library's
+// derive(Debug) would: `Name { field: value }` through debug_struct,
+// `Name(value)` through debug_tuple, or just the name.
+//
+// Every variant of an enum is formatted according to its own shape, and
+// #[debug = "..."] applies to the fields of a variant the same as to those of
+// a struct.
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Rgb(u8, #[debug = "{:#04x}"] u8, u8);
+
+#[derive(CustomDebug)]
+pub struct Marker;
+
+#[derive(CustomDebug)]
+pub struct Empty {}
+
+#[derive(CustomDebug)]
+pub enum Message<T> {
+    Quit,
+    Move {
+        x: i32,
+        #[debug = "{:+}"]
+        y: i32,
+    },
+    Write(String),
+    Color(u8, #[debug = "0b{:08b}"] u8),
+    Payload {
+        value: T,
+    },
+}
+
+#[derive(CustomDebug)]
+pub enum Never {}
+
+fn main() {
+    assert_eq!(format!("{:?}", Rgb(1, 2, 3)), "Rgb(1, 0x02, 3)");
+    assert_eq!(format!("{:?}", Marker), "Marker");
+    assert_eq!(format!("{:?}", Empty {}), "Empty");
+
+    let messages: [Message<&str>; 5] = [
+        Message::Quit,
+        Message::Move { x: 1, y: 2 },
+        Message::Write("hi".to_owned()),
+        Message::Color(7, 5),
+        Message::Payload { value: "data" },
+    ];
+    let debug: Vec<String> = messages.iter().map(|m| format!("{:?}", m)).collect();
+    assert_eq!(
+        debug,
+        [
+            "Quit",
+            "Move { x: 1, y: +2 }",
+            r#"Write("hi")"#,
+            "Color(7, 0b00000101)",
+            r#"Payload { value: "data" }"#,
+        ],
+    );
+
+    assert_eq!(
+        format!("{:#?}", Message::<u8>::Move { x: 1, y: -2 }),
+        "Move {\n    x: 1,\n    y: -2,\n}",
+    );
+
+    fn assert_debug<T: std::fmt::Debug>() {}
+    assert_debug::<Never>();
+}
@@ -0,0 +1,67 @@
+// Fields holding secrets must not end up in logs. #[debug(skip)] leaves a
+// field out of the output entirely, while #[debug(redact)] keeps its name but
+// prints `[redacted]` in place of the value, or the text given with
+// #[debug(redact = "...")].
+//
+// A struct or enum level #[debug(non_exhaustive)] ends the output of every
+// struct or variant with `..`, the way finish_non_exhaustive() does, to show
+// that not every field is listed.
+//
+// Neither a skipped nor a redacted field is ever formatted, so its type does
+// not need to implement Debug.
+
+#![allow(dead_code)]
+
+use derive_debug::CustomDebug;
+use std::fmt::Debug;
+
+pub struct Secret(String);
+
+#[derive(CustomDebug)]
+pub struct Credentials {
+    user: String,
+    #[debug(redact)]
+    password: Secret,
+    #[debug(redact = "***")]
+    token: Secret,
+    #[debug(skip)]
+    cache: Vec<Secret>,
+}
+
+#[derive(CustomDebug)]
+#[debug(non_exhaustive)]
+pub enum Request<T> {
+    Login(String, #[debug(skip)] T),
+    Logout {
+        user: String,
+        #[debug(skip)]
+        session: T,
+    },
+    Ping,
+}
+
+fn assert_debug<T: Debug>() {}
+
+fn main() {
+    let credentials = Credentials {
+        user: "admin".to_owned(),
+        password: Secret("hunter2".to_owned()),
+        token: Secret("abc".to_owned()),
+        cache: Vec::new(),
+    };
+    assert_eq!(
+        format!("{:?}", credentials),
+        r#"Credentials { user: "admin", password: [redacted], token: *** }"#,
+    );
+
+    // Secret is not Debug, and only appears in skipped fields.
+    assert_debug::<Request<Secret>>();
+    let login = Request::Login("admin".to_owned(), Secret(String::new()));
+    assert_eq!(format!("{:?}", login), r#"Login("admin", ..)"#);
+    let logout = Request::Logout {
+        user: "admin".to_owned(),
+        session: Secret(String::new()),
+    };
+    assert_eq!(format!("{:?}", logout), r#"Logout { user: "admin", .. }"#);
+    assert_eq!(format!("{:?}", Request::<Secret>::Ping), "Ping { .. }");
+}
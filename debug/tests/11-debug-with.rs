@@ -0,0 +1,76 @@
+// Some values are best shown in a form their Debug impl does not give, such
+// as bytes in hex, and some types have no Debug impl at all. With
+//
+//     #[debug(with = "path::to::function")]
+//
+// the field is printed by calling the given function, which takes a reference
+// to the field and the formatter, the same way Debug::fmt does:
+//
+//     fn function(value: &FieldType, f: &mut fmt::Formatter) -> fmt::Result
+//
+// The derive passes the debug builders a value whose Debug impl calls the
+// function, so that other fields keep their usual output and flags such as
+// {:#?} still apply around it. As the field's own Debug impl is never used,
+// its type does not get a Debug bound.
+
+use derive_debug::CustomDebug;
+use std::fmt::{self, Debug};
+
+fn hex(bytes: &Vec<u8>, f: &mut fmt::Formatter) -> fmt::Result {
+    for byte in bytes {
+        write!(f, "{:02x}", byte)?;
+    }
+    Ok(())
+}
+
+mod opaque {
+    use std::fmt;
+
+    pub fn fmt<T>(_: &T, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("<opaque>")
+    }
+}
+
+pub struct Connection;
+
+#[derive(CustomDebug)]
+pub struct Packet {
+    id: u32,
+    #[debug(with = "hex")]
+    payload: Vec<u8>,
+}
+
+#[derive(CustomDebug)]
+pub enum Peer<T> {
+    Local(#[debug(with = "opaque::fmt")] T),
+    Remote {
+        address: String,
+        #[debug(with = "opaque::fmt")]
+        connection: T,
+    },
+}
+
+fn assert_debug<T: Debug>() {}
+
+fn main() {
+    let packet = Packet {
+        id: 7,
+        payload: vec![0xde, 0xad, 0xbe, 0xef],
+    };
+    assert_eq!(
+        format!("{:?}", packet),
+        "Packet { id: 7, payload: deadbeef }",
+    );
+
+    // Connection is not Debug, and is only printed through opaque::fmt.
+    assert_debug::<Peer<Connection>>();
+    assert_eq!(format!("{:?}", Peer::Local(Connection)), "Local(<opaque>)");
+    let remote = Peer::Remote {
+        address: "10.0.0.1".to_owned(),
+        connection: Connection,
+    };
+    assert_eq!(
+        format!("{:?}", remote),
+        r#"Remote { address: "10.0.0.1", connection: <opaque> }"#,
+    );
+}
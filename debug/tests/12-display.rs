@@ -0,0 +1,90 @@
+// Error types are printed to users with Display far more often than with
+// Debug, and writing each impl by hand gets long. derive(CustomDisplay), from
+// the same crate, takes a format string for each struct or enum variant:
+//
+//     #[derive(CustomDisplay)]
+//     pub enum Error {
+//         #[display("Error reading {path}: {source}")]
+//         Read { path: String, source: io::Error },
+//         #[display("Unexpected byte {0:#04x} at offset {1}")]
+//         Parse(u8, usize),
+//     }
+//
+// The string refers to named fields by name and to tuple fields by index,
+// or with `{}` in order, as format! does with its arguments. Format specs,
+// including widths taken from a field with `width$`, work as usual.
+//
+// As with CustomDebug, type parameters get a bound on the trait they are
+// formatted with, here Display or Debug, but only when a field whose type
+// mentions them appears in a format string.
+
+use derive_debug::CustomDisplay;
+use std::fmt::Display;
+
+pub struct NotDisplay;
+
+#[derive(CustomDisplay)]
+pub enum Error<T> {
+    #[display("Error reading {path}: {source}")]
+    Read { path: String, source: String },
+    #[display("Unexpected byte {0:#04x} at offset {1}")]
+    Parse(u8, usize),
+    #[display("{} of {}")]
+    Limit(usize, usize),
+    #[display("Bad value {value:?}")]
+    Value { value: T },
+    #[display("Timed out after {{{secs}}}s")]
+    Timeout { secs: u64, context: NotDisplay },
+    #[display("Aborted")]
+    Aborted,
+}
+
+#[derive(CustomDisplay)]
+#[display("{name:>width$}|")]
+pub struct Padded {
+    name: String,
+    width: usize,
+}
+
+#[derive(CustomDisplay)]
+#[display("{0} ({1})")]
+pub struct Pair<A, B>(A, B);
+
+fn assert_display<T: Display>() {}
+
+fn main() {
+    let read = Error::<()>::Read {
+        path: "/etc/hosts".to_owned(),
+        source: "permission denied".to_owned(),
+    };
+    assert_eq!(
+        read.to_string(),
+        "Error reading /etc/hosts: permission denied",
+    );
+    assert_eq!(
+        Error::<()>::Parse(0xf, 12).to_string(),
+        "Unexpected byte 0x0f at offset 12",
+    );
+    assert_eq!(Error::<()>::Limit(3, 2).to_string(), "3 of 2");
+    assert_eq!(
+        Error::Value { value: vec![1, 2] }.to_string(),
+        "Bad value [1, 2]",
+    );
+    let timeout = Error::<()>::Timeout {
+        secs: 30,
+        context: NotDisplay,
+    };
+    assert_eq!(timeout.to_string(), "Timed out after {30}s");
+    assert_eq!(Error::<()>::Aborted.to_string(), "Aborted");
+
+    let padded = Padded {
+        name: "id".to_owned(),
+        width: 5,
+    };
+    assert_eq!(padded.to_string(), "   id|");
+
+    assert_eq!(Pair("a", 1).to_string(), "a (1)");
+
+    // T is only formatted with {:?}, so it needs Debug but not Display.
+    assert_display::<Error<Vec<u8>>>();
+}
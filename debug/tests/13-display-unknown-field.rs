@@ -0,0 +1,12 @@
+// A format string referring to a field which does not exist should be
+// reported at the string, naming the field.
+
+use derive_debug::CustomDisplay;
+
+#[derive(CustomDisplay)]
+pub enum Error {
+    #[display("Error reading {path}: {sourse}")]
+    Read { path: String, source: String },
+}
+
+fn main() {}
@@ -0,0 +1,112 @@
+// The inferred bounds follow type parameters down through the types of the
+// fields which are formatted, and bound only what those fields need:
+//
+// - `Rc<RefCell<T>>` needs `T: Debug`, like `T` itself.
+// - A parameter which only appears in skipped or redacted fields, or in
+//   fields printed through a `with` function, gets no bound at all.
+// - Qualified paths such as `<T as Trait>::Value` are bounded as a whole,
+//   just like `T::Value`, leaving `T` unbounded.
+// - Function pointers and raw pointers are Debug whatever their parameters.
+// - A field with #[debug = "..."] is bounded on the traits its format string
+//   calls, such as LowerHex for "0x{:04x}", rather than on Debug.
+//
+// Bounds which the struct already states, inline or in its where clause, are
+// not repeated.
+
+#![allow(dead_code)]
+
+use derive_debug::CustomDebug;
+use std::cell::RefCell;
+use std::fmt::{self, Debug};
+use std::rc::Rc;
+
+pub trait Trait {
+    type Value;
+}
+
+#[derive(CustomDebug)]
+pub struct Shared<T, U> {
+    value: Rc<RefCell<T>>,
+    #[debug(skip)]
+    cache: Rc<RefCell<U>>,
+}
+
+#[derive(CustomDebug)]
+pub struct Qualified<T: Trait> {
+    value: <T as Trait>::Value,
+    values: Vec<(T::Value, usize)>,
+}
+
+#[derive(CustomDebug)]
+pub struct Callbacks<T> {
+    filter: fn(&T) -> bool,
+    current: *const T,
+    #[debug(with = "opaque")]
+    state: Box<T>,
+}
+
+#[derive(CustomDebug)]
+pub struct Register<T> {
+    #[debug = "0x{:04x}"]
+    value: T,
+}
+
+/// Implements LowerHex but not Debug.
+pub struct Word(u16);
+
+impl fmt::LowerHex for Word {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+#[derive(CustomDebug)]
+pub struct Stated<T: Debug, U>
+where
+    U: fmt::Debug,
+{
+    first: Option<T>,
+    second: Vec<U>,
+}
+
+fn opaque<T>(_: &T, f: &mut fmt::Formatter) -> fmt::Result {
+    f.write_str("..")
+}
+
+fn assert_debug<F: Debug>() {}
+
+fn main() {
+    // Implements neither Debug nor Trait in a way that would need it.
+    struct Opaque;
+
+    impl Trait for Opaque {
+        type Value = u8;
+    }
+
+    assert_debug::<Shared<u8, Opaque>>();
+    assert_debug::<Qualified<Opaque>>();
+    assert_debug::<Callbacks<Opaque>>();
+    assert_debug::<Stated<u8, u8>>();
+    assert_debug::<Register<Word>>();
+
+    let register = Register { value: Word(0xbe) };
+    assert_eq!(format!("{:?}", register), "Register { value: 0x00be }");
+
+    let shared = Shared {
+        value: Rc::new(RefCell::new(1)),
+        cache: Rc::new(RefCell::new(Opaque)),
+    };
+    assert_eq!(
+        format!("{:?}", shared),
+        "Shared { value: RefCell { value: 1 } }",
+    );
+
+    let qualified = Qualified::<Opaque> {
+        value: 2,
+        values: vec![(3, 4)],
+    };
+    assert_eq!(
+        format!("{:?}", qualified),
+        "Qualified { value: 2, values: [(3, 4)] }",
+    );
+}
@@ -0,0 +1,14 @@
+// When a type parameter appears inside a trait object, whether the field can
+// be formatted does not follow from any bound on the parameter. Rather than
+// guess, the derive reports the field and asks for the bounds to be given
+// with #[debug(bound = "...")].
+
+use derive_debug::CustomDebug;
+
+#[derive(CustomDebug)]
+pub struct Handler<T> {
+    name: String,
+    callback: Box<dyn Fn(T)>,
+}
+
+fn main() {}
@@ -0,0 +1,65 @@
+// As with CustomDebug, when the inferred bounds are not the right ones, or
+// inference gives up on a field, #[display(bound = "...")] on the struct or
+// enum gives the where clause of the impl in their place. It sits next to the
+// format string of a struct:
+//
+//     #[derive(CustomDisplay)]
+//     #[display("{0}")]
+//     #[display(bound = "")]
+//     pub struct Wrapper<T>(Box<dyn Shape<T>>);
+//
+// A type parameter inside a trait object such as `dyn Shape<T>` is out of
+// reach of the inference, but the caller may know that the trait object is
+// Display whatever the parameter.
+
+use derive_debug::CustomDisplay;
+use std::fmt::{self, Display};
+
+pub trait Shape<T> {
+    fn name(&self) -> &'static str;
+}
+
+impl<T> Display for dyn Shape<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+pub struct Square;
+
+impl<T> Shape<T> for Square {
+    fn name(&self) -> &'static str {
+        "square"
+    }
+}
+
+#[derive(CustomDisplay)]
+#[display("{0}")]
+#[display(bound = "")]
+pub struct Wrapper<T>(Box<dyn Shape<T>>);
+
+#[derive(CustomDisplay)]
+#[display(bound = "T: Display")]
+pub enum Drawing<T> {
+    #[display("{label}: {shape}")]
+    Labeled { label: T, shape: Box<dyn Shape<T>> },
+    #[display("empty")]
+    Empty,
+}
+
+pub struct NotDisplay;
+
+fn assert_display<T: Display>() {}
+
+fn main() {
+    assert_display::<Wrapper<NotDisplay>>();
+    let wrapper = Wrapper::<NotDisplay>(Box::new(Square));
+    assert_eq!(wrapper.to_string(), "square");
+
+    let drawing = Drawing::Labeled {
+        label: 1,
+        shape: Box::new(Square),
+    };
+    assert_eq!(drawing.to_string(), "1: square");
+    assert_eq!(Drawing::<u8>::Empty.to_string(), "empty");
+}
@@ -1,12 +1,20 @@
 #[test]
 fn tests() {
     let t = trybuild::TestCases::new();
-    //t.pass("tests/01-parse.rs");
-    //t.pass("tests/02-impl-debug.rs");
-    //t.pass("tests/03-custom-format.rs");
-    //t.pass("tests/04-type-parameter.rs");
-    //t.pass("tests/05-phantom-data.rs");
-    //t.pass("tests/06-bound-trouble.rs");
-    //t.pass("tests/07-associated-type.rs");
-    //t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/01-parse.rs");
+    t.pass("tests/02-impl-debug.rs");
+    t.pass("tests/03-custom-format.rs");
+    t.pass("tests/04-type-parameter.rs");
+    t.pass("tests/05-phantom-data.rs");
+    t.pass("tests/06-bound-trouble.rs");
+    t.pass("tests/07-associated-type.rs");
+    t.pass("tests/08-escape-hatch.rs");
+    t.pass("tests/09-enums-and-tuples.rs");
+    t.pass("tests/10-skip-and-redact.rs");
+    t.pass("tests/11-debug-with.rs");
+    t.pass("tests/12-display.rs");
+    t.compile_fail("tests/13-display-unknown-field.rs");
+    t.pass("tests/14-nested-bounds.rs");
+    t.compile_fail("tests/15-unreachable-bound.rs");
+    t.pass("tests/16-display-bound.rs");
 }